@@ -0,0 +1,132 @@
+use std::ops::RangeInclusive;
+
+use crate::{create_color_vec, create_threshold_map, find_max_values, Game, ThresholdMap};
+
+// --- batch evaluation ---
+// games are reduced once to their per-color maxima, a game being possible
+// with a bag if and only if the bag covers them
+pub struct BatchEvaluator {
+    minimal_cube_sets: Vec<(i32, ThresholdMap)>,
+}
+
+impl BatchEvaluator {
+    pub fn new(games: &[Game]) -> Self {
+        BatchEvaluator {
+            minimal_cube_sets: games.iter().map(|game| (game.id, find_max_values(game))).collect(),
+        }
+    }
+
+    // part 1 answer for a single bag
+    pub fn evaluate(&self, bag: &ThresholdMap) -> i32 {
+        let colors = create_color_vec();
+
+        self.minimal_cube_sets
+        .iter()
+        .filter(|(_, max_values)| colors.iter().all(|color| max_values[*color] <= bag[*color]))
+        .map(|(id, _)| id)
+        .sum()
+    }
+
+    // part 1 answer for each bag, in the same order
+    pub fn evaluate_all(&self, bags: &[ThresholdMap]) -> Vec<i32> {
+        bags.iter().map(|bag| self.evaluate(bag)).collect()
+    }
+
+    pub fn sweep(
+        &self,
+        red: RangeInclusive<i32>,
+        green: RangeInclusive<i32>,
+        blue: RangeInclusive<i32>,
+    ) -> Sweep {
+        let mut results = vec![];
+
+        for blue in blue.clone() {
+            for green in green.clone() {
+                for red in red.clone() {
+                    let bag = create_threshold_map(red, green, blue);
+                    let sum = self.evaluate(&bag);
+
+                    results.push(((red, green, blue), sum));
+                }
+            }
+        }
+
+        Sweep { red, green, blue, results }
+    }
+}
+
+// --- sweep ---
+pub struct Sweep {
+    red: RangeInclusive<i32>,
+    green: RangeInclusive<i32>,
+    blue: RangeInclusive<i32>,
+    // ((red, green, blue), part 1 sum), blue varying the slowest and red the fastest,
+    // so a bag is found from its offsets to the start of each range
+    results: Vec<((i32, i32, i32), i32)>,
+}
+
+impl Sweep {
+    pub fn get(&self, red: i32, green: i32, blue: i32) -> Option<i32> {
+        if !self.red.contains(&red) || !self.green.contains(&green) || !self.blue.contains(&blue) {
+            return None;
+        }
+
+        let red_count = (self.red.end() - self.red.start() + 1) as usize;
+        let green_count = (self.green.end() - self.green.start() + 1) as usize;
+
+        let red_offset = (red - self.red.start()) as usize;
+        let green_offset = (green - self.green.start()) as usize;
+        let blue_offset = (blue - self.blue.start()) as usize;
+
+        self.results
+        .get((blue_offset * green_count + green_offset) * red_count + red_offset)
+        .map(|(_, sum)| *sum)
+    }
+
+    // one line per bag: "red,green,blue,sum"
+    pub fn to_table(&self) -> String {
+        let mut table = vec!["red,green,blue,sum".to_string()];
+
+        for ((red, green, blue), sum) in &self.results {
+            table.push(format!("{},{},{},{}", red, green, blue, sum));
+        }
+
+        table.join("\n")
+    }
+
+    // one red x green grid per blue value, each cell shaded
+    // from ' ' (lowest sum) to '@' (highest sum) of the whole sweep
+    pub fn to_heatmap(&self) -> String {
+        const SHADES: [char; 10] = [' ', '.', ':', '-', '=', '+', '*', '#', '%', '@'];
+
+        let min = self.results.iter().map(|(_, sum)| *sum).min().unwrap_or(0);
+        let max = self.results.iter().map(|(_, sum)| *sum).max().unwrap_or(0);
+
+        let shade = |sum: i32| {
+            if max == min {
+                return SHADES[SHADES.len() - 1];
+            }
+
+            SHADES[((sum - min) as usize * (SHADES.len() - 1)) / (max - min) as usize]
+        };
+
+        let mut lines = vec![];
+
+        for blue in self.blue.clone() {
+            lines.push(format!("blue = {} (rows: green, columns: red {}..={})", blue, self.red.start(), self.red.end()));
+
+            for green in self.green.clone() {
+                let row = self.red
+                .clone()
+                .map(|red| shade(self.get(red, green, blue).unwrap()))
+                .collect::<String>();
+
+                lines.push(format!("{:>4} |{}|", green, row));
+            }
+        }
+
+        lines.push(format!("scale: ' ' = {}, '@' = {}", min, max));
+
+        lines.join("\n")
+    }
+}
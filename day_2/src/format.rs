@@ -0,0 +1,149 @@
+use std::{error::Error, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{try_parse_games, Draw, Game};
+
+// --- formats ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    // the original puzzle syntax, one "Game N: ..." per line
+    Text,
+    // a JSON array of games with their nested draws
+    Json,
+    // one row per draw, with the game ID repeated on each row
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" | "txt" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("Unknown format: {}", s)),
+        }
+    }
+}
+
+impl Format {
+    pub fn write(&self, games: &[Game]) -> String {
+        match self {
+            Format::Text => to_text(games),
+            Format::Json => to_json(games),
+            Format::Csv => to_csv(games),
+        }
+    }
+
+    pub fn read(&self, input: &str) -> Result<Vec<Game>, Box<dyn Error>> {
+        match self {
+            Format::Text => Ok(try_parse_games(input)?),
+            Format::Json => Ok(from_json(input)?),
+            Format::Csv => from_csv(input),
+        }
+    }
+}
+
+// --- text ---
+fn to_text(games: &[Game]) -> String {
+    games
+    .iter()
+    .map(|game| game.to_string())
+    .collect::<Vec<String>>()
+    .join("\n")
+}
+
+// --- JSON ---
+fn to_json(games: &[Game]) -> String {
+    serde_json::to_string_pretty(games).expect("Could not serialize games")
+}
+
+fn from_json(input: &str) -> serde_json::Result<Vec<Game>> {
+    serde_json::from_str(input)
+}
+
+// --- CSV ---
+// a flat record, so games can be loaded as-is in a spreadsheet,
+// a game without draws is kept as a single row with draw 0
+#[derive(Debug, Serialize, Deserialize)]
+struct DrawRecord {
+    game: i32,
+    draw: usize,
+    red: i32,
+    green: i32,
+    blue: i32,
+}
+
+fn to_csv(games: &[Game]) -> String {
+    let mut writer = csv::Writer::from_writer(vec![]);
+
+    for game in games {
+        if game.draws.is_empty() {
+            writer
+            .serialize(DrawRecord {
+                game: game.id,
+                draw: 0,
+                red: 0,
+                green: 0,
+                blue: 0,
+            })
+            .expect("Could not serialize game");
+        }
+
+        for (index, draw) in game.draws.iter().enumerate() {
+            writer
+            .serialize(DrawRecord {
+                game: game.id,
+                draw: index + 1,
+                red: draw.red,
+                green: draw.green,
+                blue: draw.blue,
+            })
+            .expect("Could not serialize draw");
+        }
+    }
+
+    let bytes = writer.into_inner().expect("Could not flush CSV writer");
+
+    String::from_utf8(bytes).expect("CSV output is not valid UTF-8")
+}
+
+// every row with draw 0 or 1 starts a new game, even with an ID already seen, so games
+// come back in file order with their duplicates, other rows continue the game right above
+fn from_csv(input: &str) -> Result<Vec<Game>, Box<dyn Error>> {
+    let mut games: Vec<Game> = Vec::new();
+
+    let mut reader = csv::Reader::from_reader(input.as_bytes());
+
+    for (index, record) in reader.deserialize().enumerate() {
+        let record: DrawRecord = record?;
+        // the header is line 1
+        let line = index + 2;
+
+        match games.last() {
+            _ if record.draw <= 1 => games.push(Game {
+                id: record.game,
+                draws: vec![],
+            }),
+            Some(game) if game.id == record.game && record.draw == game.draws.len() + 1 => (),
+            _ => {
+                return Err(format!("Draw {} of game {} does not follow the row above (line {})", record.draw, record.game, line).into());
+            }
+        }
+
+        // the placeholder row of a game without draws
+        if record.draw == 0 {
+            continue;
+        }
+
+        games.last_mut().unwrap().draws.push(Draw {
+            red: record.red,
+            green: record.green,
+            blue: record.blue,
+        });
+    }
+
+    Ok(games)
+}
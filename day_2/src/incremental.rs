@@ -0,0 +1,154 @@
+use std::{
+    collections::HashMap,
+    fs::{File, Metadata},
+    io::{Read, Seek, SeekFrom},
+};
+
+use crate::{compute_power, find_max_values, is_game_possible, parse_game_line, Game, ThresholdMap};
+
+// --- incremental evaluation ---
+// keeps the part 1 and part 2 sums up to date while game lines arrive,
+// a line for an already known game ID replaces it, and "retract Game N" removes it
+pub struct IncrementalEvaluator {
+    threshold_map: ThresholdMap,
+    games: HashMap<i32, Game>,
+    id_sum: i32,
+    power_sum: i32,
+    // tailing state: the file being read, bytes of it already read, and the last line
+    // if not terminated yet, kept as bytes since a read can stop in the middle of a character
+    file_id: Option<u64>,
+    offset: u64,
+    pending: Vec<u8>,
+}
+
+impl IncrementalEvaluator {
+    pub fn new(threshold_map: ThresholdMap) -> Self {
+        IncrementalEvaluator {
+            threshold_map,
+            games: HashMap::new(),
+            id_sum: 0,
+            power_sum: 0,
+            file_id: None,
+            offset: 0,
+            pending: vec![],
+        }
+    }
+
+    // part 1 answer for the games ingested so far
+    pub fn id_sum(&self) -> i32 {
+        self.id_sum
+    }
+
+    // part 2 answer for the games ingested so far
+    pub fn power_sum(&self) -> i32 {
+        self.power_sum
+    }
+
+    pub fn game_count(&self) -> usize {
+        self.games.len()
+    }
+
+    pub fn ingest_line(&mut self, line: &str) {
+        let line = line.trim();
+
+        if line.is_empty() {
+            return;
+        }
+
+        if let Some(game_info) = line.strip_prefix("retract ") {
+            let game_id = game_info
+            .replace("Game ", "")
+            .parse::<i32>()
+            .unwrap();
+
+            self.retract(game_id);
+        } else {
+            self.upsert(parse_game_line(line));
+        }
+    }
+
+    // adds a game, or replaces the game with the same ID
+    pub fn upsert(&mut self, game: Game) {
+        self.retract(game.id);
+        self.add_contribution(&game, 1);
+        self.games.insert(game.id, game);
+    }
+
+    pub fn retract(&mut self, game_id: i32) -> Option<Game> {
+        let game = self.games.remove(&game_id)?;
+        self.add_contribution(&game, -1);
+
+        Some(game)
+    }
+
+    fn add_contribution(&mut self, game: &Game, sign: i32) {
+        if is_game_possible(game, &self.threshold_map) {
+            self.id_sum += sign * game.id;
+        }
+
+        self.power_sum += sign * compute_power(&find_max_values(game));
+    }
+
+    // reads what was appended to the file since the last call, and ingests
+    // every complete line, returns the number of lines ingested
+    pub fn read_appended(&mut self, file_path: &str) -> usize {
+        let mut file = File::open(file_path).expect("Could not open file");
+        let metadata = file.metadata().expect("Could not read file metadata");
+        let file_id = file_identity(&metadata);
+
+        // the file was truncated, or replaced by another one (e.g. rotated), so we start
+        // over from the beginning, forgetting the games that may no longer be in it
+        if metadata.len() < self.offset || (self.file_id.is_some() && file_id != self.file_id) {
+            *self = IncrementalEvaluator::new(self.threshold_map.clone());
+        }
+
+        self.file_id = file_id;
+
+        file.seek(SeekFrom::Start(self.offset)).expect("Could not seek file");
+
+        let read = file.read_to_end(&mut self.pending).expect("Could not read file");
+        self.offset += read as u64;
+
+        // the last piece is kept back until its line is terminated
+        let complete = match self.pending.iter().rposition(|byte| *byte == b'\n') {
+            Some(end) => self.pending.drain(..=end).collect::<Vec<u8>>(),
+            None => return 0,
+        };
+
+        let complete = String::from_utf8(complete).expect("File is not valid UTF-8");
+        let lines = complete.lines().collect::<Vec<&str>>();
+
+        for line in &lines {
+            self.ingest_line(line);
+        }
+
+        lines.len()
+    }
+
+    // ingests the last line even if it is not terminated, e.g. once the file is complete
+    pub fn flush(&mut self) -> usize {
+        let line = String::from_utf8(std::mem::take(&mut self.pending)).expect("File is not valid UTF-8");
+
+        if line.trim().is_empty() {
+            return 0;
+        }
+
+        self.ingest_line(&line);
+
+        1
+    }
+}
+
+// the inode of the file, which changes when the file is replaced rather than written to,
+// other platforms only notice a replacement by a shorter file
+#[cfg(unix)]
+fn file_identity(metadata: &Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some(metadata.ino())
+}
+
+#[cfg(not(unix))]
+fn file_identity(_metadata: &Metadata) -> Option<u64> {
+    None
+}
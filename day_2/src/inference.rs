@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+use crate::{covers, create_color_vec, create_threshold_map, find_max_values, Game, ThresholdMap};
+
+// --- inference ---
+// the set of bags consistent with a labelling: every bag holding at least
+// `minimal_bag` cubes of each color, and covering none of the `excluded` cube sets
+#[derive(Debug)]
+pub struct BagRegion {
+    pub minimal_bag: ThresholdMap,
+    pub excluded: Vec<ThresholdMap>,
+}
+
+impl BagRegion {
+    pub fn contains(&self, bag: &ThresholdMap) -> bool {
+        covers(bag, &self.minimal_bag) && !self.excluded.iter().any(|cubes| covers(bag, cubes))
+    }
+}
+
+// proof that no bag fits the labelling
+#[derive(Debug)]
+pub struct Counterexample {
+    // the game labelled impossible that the smallest candidate bag still allows
+    pub impossible_game: i32,
+    // for each color, the possible game forcing the bag up to that count
+    pub forcing_games: HashMap<String, i32>,
+}
+
+#[derive(Debug)]
+pub enum InferenceError {
+    // labelled IDs matching no game, a typo would otherwise silently loosen the region
+    UnknownGames(Vec<i32>),
+    Inconsistent(Counterexample),
+}
+
+// a game is possible with a bag if and only if the bag covers the game's per-color maxima,
+// so possible games give a lower bound for the bag, and each impossible game
+// removes every bag covering its own maxima
+pub fn infer_bag(games: &[Game], possible: &[i32], impossible: &[i32]) -> Result<BagRegion, InferenceError> {
+    let colors = create_color_vec();
+
+    let unknown_games = possible
+    .iter()
+    .chain(impossible)
+    .filter(|id| !games.iter().any(|game| game.id == **id))
+    .copied()
+    .collect::<Vec<i32>>();
+
+    if !unknown_games.is_empty() {
+        return Err(InferenceError::UnknownGames(unknown_games));
+    }
+
+    // minimal cube set of every labelled game
+    let possible_games = games
+    .iter()
+    .filter(|game| possible.contains(&game.id))
+    .map(|game| (game.id, find_max_values(game)))
+    .collect::<Vec<(i32, ThresholdMap)>>();
+
+    let impossible_games = games
+    .iter()
+    .filter(|game| impossible.contains(&game.id))
+    .map(|game| (game.id, find_max_values(game)))
+    .collect::<Vec<(i32, ThresholdMap)>>();
+
+    // the smallest bag allowing every possible game
+    let mut minimal_bag = create_threshold_map(0, 0, 0);
+
+    for (_, max_values) in &possible_games {
+        for color in &colors {
+            let value = max_values[*color];
+
+            if value > minimal_bag[*color] {
+                minimal_bag.insert(color.to_string(), value);
+            }
+        }
+    }
+
+    // every bag in the region covers the minimal bag, so if the minimal bag
+    // allows an impossible game, every other bag does as well
+    if let Some((impossible_game, _)) = impossible_games
+    .iter()
+    .find(|(_, max_values)| covers(&minimal_bag, max_values))
+    {
+        let forcing_games = colors
+        .iter()
+        .filter_map(|color| {
+            possible_games
+            .iter()
+            .find(|(_, max_values)| minimal_bag[*color] > 0 && max_values[*color] == minimal_bag[*color])
+            .map(|(id, _)| (color.to_string(), *id))
+        })
+        .collect();
+
+        return Err(InferenceError::Inconsistent(Counterexample {
+            impossible_game: *impossible_game,
+            forcing_games,
+        }));
+    }
+
+    Ok(BagRegion {
+        minimal_bag,
+        excluded: impossible_games.into_iter().map(|(_, max_values)| max_values).collect(),
+    })
+}
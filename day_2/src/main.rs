@@ -1,47 +1,49 @@
-use std::{fs::read_to_string, collections::HashMap};
+use std::{fs::read_to_string, collections::HashMap, fmt};
+
+use serde::{Deserialize, Serialize};
+
+mod batch;
+mod format;
+mod incremental;
+mod inference;
+mod pareto;
+mod query;
+mod report;
+mod sequence;
+mod simulation;
+mod statistics;
+mod validation;
+
+use format::Format;
 
 const PUZZLE_INPUT: &str = "input.txt";
 const EXAMPLE_INPUT_PART_1: &str = "example1.txt";
 const EXAMPLE_INPUT_PART_2: &str = "example2.txt";
+const EXAMPLE_INPUT_INVALID: &str = "example_invalid.txt";
 
 // --- execution parts ---
 fn part_1(file_path: &str, threshold_map: ThresholdMap) -> i32 {
     // parse file
     let parsed_games = parse_game_file(file_path);
 
-    // iterate over games and filter operation result
-    parsed_games
-    .iter()
-    .filter_map(|game| {
-        // get game ID, if the game is valid it will be added
-        let game_id = game.id;
-
-        let mut iterator = 0;
-        let mut over_limit = false;
-
-        // loop over draws and check if any of the values is over the threshold
-        // if it is, the game is invalid
-        while (iterator < game.draws.len()) && !over_limit {
-            let draw = &game.draws[iterator];
-
-            let red = draw.red;
-            let green = draw.green;
-            let blue = draw.blue;
-
-            let red_threshold = threshold_map.get("red").unwrap();
-            let green_threshold = threshold_map.get("green").unwrap();
-            let blue_threshold = threshold_map.get("blue").unwrap();
+    sum_possible_game_ids(&parsed_games, &threshold_map)
+}
 
-            if red > *red_threshold || green > *green_threshold || blue > *blue_threshold {
-                over_limit = true;
-            }
+fn part_2(file_path: &str) -> i32 {
+        // parse file
+        let parsed_games = parse_game_file(file_path);
 
-            iterator += 1;
-        }
+        sum_powers(&parsed_games)
+}
 
+fn sum_possible_game_ids(games: &[Game], threshold_map: &ThresholdMap) -> i32 {
+    // iterate over games and filter operation result
+    games
+    .iter()
+    .filter_map(|game| {
         // if the game is valid, return the game ID for the sum
-        if !over_limit {
-            Some(game_id)
+        if is_game_possible(game, threshold_map) {
+            Some(game.id)
         } else {
             None
         }
@@ -49,55 +51,13 @@ fn part_1(file_path: &str, threshold_map: ThresholdMap) -> i32 {
     .sum()
 }
 
-fn part_2(file_path: &str) -> i32 {
-        // parse file
-        let parsed_games = parse_game_file(file_path);
-
-        // iterate over games and filter operation result
-        parsed_games
-        .iter()
-        .map(|game| {
-            // setup a zeroed map of color to store max values for each color
-            let mut max_values = create_threshold_map(0, 0, 0);
-
-            // iterate over draws and check if any of the values is over the threshold
-            // if it is, replace the value in the map with the current value
-            for draw in &game.draws {
-                let red = draw.red;
-                let green = draw.green;
-                let blue = draw.blue;
-
-                // BOOOOOOOOOOOOOO clone is for squares
-                // but did not wanted to deal with lifetimes so be it
-                let cloned = max_values.clone();
-
-                // getting current max values
-                let red_threshold = cloned.get("red").unwrap();
-                let green_threshold = cloned.get("green").unwrap();
-                let blue_threshold = cloned.get("blue").unwrap();
-
-                // checking if current value is greater than the threshold
-                if red > *red_threshold {
-                    max_values.insert("red".to_string(), red);
-                }
-                if green > *green_threshold {
-                    max_values.insert("green".to_string(), green);
-                }
-                if blue > *blue_threshold {
-                    max_values.insert("blue".to_string(), blue);
-                }
-            }
-
-            // get the max values from the map
-            let red_threshold = max_values.get("red").unwrap();
-            let green_threshold = max_values.get("green").unwrap();
-            let blue_threshold = max_values.get("blue").unwrap();
-
-            // multiply the max values
-            red_threshold * green_threshold * blue_threshold
-        })
-        // sum it
-        .sum()
+fn sum_powers(games: &[Game]) -> i32 {
+    // iterate over games and filter operation result
+    games
+    .iter()
+    .map(|game| compute_power(&find_max_values(game)))
+    // sum it
+    .sum()
 }
 
 // --- Helpers ---
@@ -107,94 +67,247 @@ fn create_color_vec<'a>() -> Vec<&'a str> {
     ["red", "green", "blue"].to_vec()
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 struct Draw {
     pub red: i32,
     pub green: i32,
     pub blue: i32,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 struct Game {
     pub id: i32,
     pub draws: Vec<Draw>,
 }
 
+// prints a draw back in the puzzle syntax, e.g. "4 red, 3 blue"
+// colors are always written in red, green, blue order and zero counts are omitted
+impl fmt::Display for Draw {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts = [self.red, self.green, self.blue];
+
+        // a draw without any cube still has to be read back as one
+        if counts.iter().all(|count| *count == 0) {
+            return write!(f, "0 red, 0 green, 0 blue");
+        }
+
+        let parts = create_color_vec()
+        .iter()
+        .zip(counts)
+        .filter(|(_, count)| *count != 0)
+        .map(|(color, count)| format!("{} {}", count, color))
+        .collect::<Vec<String>>();
+
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+// prints a game back in the puzzle syntax, e.g. "Game 1: 4 red, 3 blue; 2 green"
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let draws = self.draws
+        .iter()
+        .map(|draw| draw.to_string())
+        .collect::<Vec<String>>();
+
+        if draws.is_empty() {
+            return write!(f, "Game {}:", self.id);
+        }
+
+        write!(f, "Game {}: {}", self.id, draws.join("; "))
+    }
+}
+
 fn parse_game_file(filepath: &str) -> Vec<Game> {
+    // read file
+    let lines = read_to_string(filepath).expect("Could not read file");
+
+    parse_games(&lines)
+}
+
+fn parse_games(lines: &str) -> Vec<Game> {
+    try_parse_games(lines).unwrap_or_else(|error| panic!("{}", error))
+}
+
+// the same as `parse_games`, with the first bad line reported instead of panicking
+fn try_parse_games(lines: &str) -> Result<Vec<Game>, String> {
     // create a vector of games
     let mut games: Vec<Game> = Vec::new();
 
+    // iterate over lines, counted from 1
+    for (index, line) in lines.lines().enumerate() {
+        if line.trim().is_empty() {
+            return Err(format!("Empty line (line {})", index + 1));
+        }
+
+        // add game to games
+        let game = try_parse_game_line(line).map_err(|error| format!("{} (line {})", error, index + 1))?;
+        games.push(game);
+    }
+
+    // return the vector of games
+    Ok(games)
+}
+
+fn parse_game_line(line: &str) -> Game {
+    try_parse_game_line(line).unwrap_or_else(|error| panic!("{}", error))
+}
+
+fn try_parse_game_line(line: &str) -> Result<Game, String> {
     // get colors
     let colors = create_color_vec();
 
-    // read file
-    let lines = read_to_string(filepath).expect("Could not read file");
+    // create the current line game
+    let mut current_game = Game::default();
+
+    // split game info and draws
+    let (game_info, draws) = line
+    .split_once(':')
+    .ok_or_else(|| format!("Missing ':' after the game ID in {:?}", line))?;
+
+    // split game info
+    let game_id = game_info
+    .trim()
+    .strip_prefix("Game ")
+    .and_then(|id| id.trim().parse::<i32>().ok())
+    .ok_or_else(|| format!("Invalid game ID in {:?}", line))?;
+
+    // set game id
+    current_game.id = game_id;
+
+    // "Game 1:" has no draws at all
+    if draws.trim().is_empty() {
+        return Ok(current_game);
+    }
+
+    // split draws
+    let draws = draws.split(';');
+
+    // iterate over draws
+    for draw in draws {
+        // create the current draw
+        let mut current_draw = Draw::default();
+
+        // split draw
+        let draw = draw.split(',');
 
-    // iterate over lines
-    for line in lines.lines() {
-        // create the current line game
-        let mut current_game = Game::default();
-
-        // split game info and draws
-        let mut game_info_draws = line.split(':');
-        let game_info = game_info_draws.next().unwrap();
-        let draws = game_info_draws.next().unwrap();
-
-        // split game info
-        let game_id = game_info
-        .replace("Game ", "")
-        .parse::<i32>()
-        .unwrap();
-
-        // set game id
-        current_game.id = game_id;
-
-        // split draws
-        let draws = draws.split(';');
-
-        // iterate over draws
-        for draw in draws {
-            // create the current draw
-            let mut current_draw = Draw::default();
-
-            // split draw
-            let draw = draw.split(',');
-
-            // iterate over draw
-            for possibilities in draw {
-                // checking color of draw
-                for color in &colors {
-                    // if color is in possibilities
-                    if possibilities.contains(color) {
-                        // get value
-                        let color_pattern = format!(" {}", color);
-                        let possible_integer = possibilities.replace(&color_pattern, "");
-
-                        let value = possible_integer
-                        .replace(' ', "")
-                        .parse::<i32>()
-                        .unwrap();
-
-                        match *color {
-                            "red" => current_draw.red = value,
-                            "green" => current_draw.green = value,
-                            "blue" => current_draw.blue = value,
-                            _ => (),
-                        }
+        // iterate over draw
+        for possibilities in draw {
+            // checking color of draw
+            for color in &colors {
+                // if color is in possibilities
+                if possibilities.contains(color) {
+                    // get value
+                    let color_pattern = format!(" {}", color);
+                    let possible_integer = possibilities.replace(&color_pattern, "");
+
+                    let value = possible_integer
+                    .replace(' ', "")
+                    .parse::<i32>()
+                    .map_err(|_| format!("Invalid {} count in {:?}", color, possibilities.trim()))?;
+
+                    match *color {
+                        "red" => current_draw.red = value,
+                        "green" => current_draw.green = value,
+                        "blue" => current_draw.blue = value,
+                        _ => (),
                     }
                 }
             }
+        }
+
+        // add draw to game
+        current_game.draws.push(current_draw);
+    }
+
+    Ok(current_game)
+}
+
+fn is_game_possible(game: &Game, threshold_map: &ThresholdMap) -> bool {
+    let mut iterator = 0;
+    let mut over_limit = false;
+
+    // loop over draws and check if any of the values is over the threshold
+    // if it is, the game is invalid
+    while (iterator < game.draws.len()) && !over_limit {
+        let draw = &game.draws[iterator];
+
+        let red = draw.red;
+        let green = draw.green;
+        let blue = draw.blue;
+
+        let red_threshold = threshold_map.get("red").unwrap();
+        let green_threshold = threshold_map.get("green").unwrap();
+        let blue_threshold = threshold_map.get("blue").unwrap();
 
-            // add draw to game
-            current_game.draws.push(current_draw);
+        if red > *red_threshold || green > *green_threshold || blue > *blue_threshold {
+            over_limit = true;
         }
 
-        // add game to games
-        games.push(current_game);
+        iterator += 1;
     }
 
-    // return the vector of games
-    games
+    !over_limit
+}
+
+// a bag covers a cube set when it holds at least as many cubes of each color
+fn covers(bag: &ThresholdMap, cubes: &ThresholdMap) -> bool {
+    create_color_vec()
+    .iter()
+    .all(|color| bag[*color] >= cubes[*color])
+}
+
+fn find_max_values(game: &Game) -> ThresholdMap {
+    // setup a zeroed map of color to store max values for each color
+    let mut max_values = create_threshold_map(0, 0, 0);
+
+    // iterate over draws and check if any of the values is over the threshold
+    // if it is, replace the value in the map with the current value
+    for draw in &game.draws {
+        let red = draw.red;
+        let green = draw.green;
+        let blue = draw.blue;
+
+        // BOOOOOOOOOOOOOO clone is for squares
+        // but did not wanted to deal with lifetimes so be it
+        let cloned = max_values.clone();
+
+        // getting current max values
+        let red_threshold = cloned.get("red").unwrap();
+        let green_threshold = cloned.get("green").unwrap();
+        let blue_threshold = cloned.get("blue").unwrap();
+
+        // checking if current value is greater than the threshold
+        if red > *red_threshold {
+            max_values.insert("red".to_string(), red);
+        }
+        if green > *green_threshold {
+            max_values.insert("green".to_string(), green);
+        }
+        if blue > *blue_threshold {
+            max_values.insert("blue".to_string(), blue);
+        }
+    }
+
+    max_values
+}
+
+fn compute_power(max_values: &ThresholdMap) -> i32 {
+    // get the max values from the map
+    let red_threshold = max_values.get("red").unwrap();
+    let green_threshold = max_values.get("green").unwrap();
+    let blue_threshold = max_values.get("blue").unwrap();
+
+    // multiply the max values
+    red_threshold * green_threshold * blue_threshold
+}
+
+fn format_threshold_map(threshold_map: &ThresholdMap) -> String {
+    create_color_vec()
+    .iter()
+    .map(|color| format!("{} {}", threshold_map[*color], color))
+    .collect::<Vec<String>>()
+    .join(", ")
 }
 
 fn create_threshold_map(red: i32, green: i32, blue: i32) -> ThresholdMap {
@@ -207,7 +320,120 @@ fn create_threshold_map(red: i32, green: i32, blue: i32) -> ThresholdMap {
     threshold_map
 }
 
+// --- commands ---
+// usage: day_2 convert <text|json|csv> <text|json|csv> <file>
+//        day_2 explain <file> <red> <green> <blue> <text|json>
+//        day_2 follow <file>
+//        day_2 validate <file> <strict|lenient>
+//        day_2 query <file> "<query>"
+//        day_2 sweep <file> <red from-to> <green from-to> <blue from-to> <table|heatmap>
+//        day_2 simulate <red> <green> <blue> <games> <draws per game> <cubes per draw> <seed>
+fn run_command(args: &[String]) {
+    match args.iter().map(String::as_str).collect::<Vec<&str>>().as_slice() {
+        ["convert", from, to, file_path] => {
+            let from = from.parse::<Format>().unwrap();
+            let to = to.parse::<Format>().unwrap();
+
+            let input = read_to_string(file_path).expect("Could not read file");
+            let games = from.read(&input).expect("Could not parse games");
+
+            println!("{}", to.write(&games));
+        }
+        ["explain", file_path, red, green, blue, output] => {
+            let games = parse_game_file(file_path);
+            let threshold_map = create_threshold_map(red.parse().unwrap(), green.parse().unwrap(), blue.parse().unwrap());
+            let explanations = report::explain_games(&games, &threshold_map);
+
+            match *output {
+                "text" => println!("{}", report::to_text(&explanations)),
+                "json" => println!("{}", report::to_json(&explanations)),
+                _ => panic!("Unknown report output: {}", output),
+            }
+        }
+        ["follow", file_path] => {
+            let mut evaluator = incremental::IncrementalEvaluator::new(create_threshold_map(12, 13, 14));
+
+            // polling the file for new lines until interrupted
+            loop {
+                if evaluator.read_appended(file_path) > 0 {
+                    println!(
+                        "{} games, part 1: {}, part 2: {}",
+                        evaluator.game_count(),
+                        evaluator.id_sum(),
+                        evaluator.power_sum()
+                    );
+                }
+
+                std::thread::sleep(std::time::Duration::from_millis(500));
+            }
+        }
+        ["validate", file_path, mode] => {
+            let mode = match *mode {
+                "strict" => validation::ValidationMode::Strict,
+                "lenient" => validation::ValidationMode::Lenient,
+                _ => panic!("Unknown validation mode: {}", mode),
+            };
+
+            match validation::load_validated_games(file_path, mode) {
+                Ok(games) => println!("{} games loaded", games.len()),
+                Err(report) => panic!("Invalid game file:\n{}", report),
+            }
+        }
+        ["query", file_path, query] => {
+            let games = parse_game_file(file_path);
+            let query = query::Query::parse(query).unwrap_or_else(|error| panic!("Invalid query: {}", error));
+
+            print!("{}", query.run(&games));
+        }
+        ["sweep", file_path, red, green, blue, output] => {
+            let games = parse_game_file(file_path);
+            let sweep = batch::BatchEvaluator::new(&games).sweep(parse_range(red), parse_range(green), parse_range(blue));
+
+            match *output {
+                "table" => println!("{}", sweep.to_table()),
+                "heatmap" => println!("{}", sweep.to_heatmap()),
+                _ => panic!("Unknown sweep output: {}", output),
+            }
+        }
+        ["simulate", red, green, blue, game_count, draws_per_game, cubes_per_draw, seed] => {
+            let simulation = simulation::Simulation {
+                bag: create_threshold_map(red.parse().unwrap(), green.parse().unwrap(), blue.parse().unwrap()),
+                draws_per_game: draws_per_game.parse().unwrap(),
+                cubes_per_draw: cubes_per_draw.parse().unwrap(),
+                seed: seed.parse().unwrap(),
+            };
+
+            let games = simulation.generate(game_count.parse().unwrap());
+            let (id_sum, power_sum) = simulation.validate(&games).unwrap();
+            eprintln!("part 1: {}, part 2: {}", id_sum, power_sum);
+
+            println!("{}", Format::Text.write(&games));
+        }
+        _ => panic!("Unknown command: {}", args.join(" ")),
+    }
+}
+
+// parses an inclusive range given as "from-to", or a single value
+fn parse_range(range: &str) -> std::ops::RangeInclusive<i32> {
+    match range.split_once('-') {
+        Some((from, to)) => from.parse().unwrap()..=to.parse().unwrap(),
+        None => {
+            let value = range.parse().unwrap();
+            value..=value
+        }
+    }
+}
+
 fn main() {
+    // commands are only run when arguments are given,
+    // otherwise we solve the puzzle as usual
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+
+    if !args.is_empty() {
+        run_command(&args);
+        return;
+    }
+
     println!("=== Advent of Code 2023 - Day 2 ===\n");
 
     println!("--- Part 1 ---");
@@ -243,4 +469,721 @@ fn main() {
     println!("PART2: Input file");
     let result = part_2(PUZZLE_INPUT);
     println!("Result: {}", result);
+
+    println!("--- Serialization ---");
+
+    println!("SERIALIZATION: Display");
+    let games = parse_game_file(EXAMPLE_INPUT_PART_1);
+    let result = games[0].to_string();
+    let expected = "Game 1: 4 red, 3 blue; 1 red, 2 green, 6 blue; 2 green";
+    println!("Test result: {} (expected: {})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("SERIALIZATION: Display round-trip, edge cases");
+    let empty_draw = Draw::default();
+    let draw = Draw { red: 4, green: 0, blue: 3 };
+    let games = vec![
+        Game { id: 1, draws: vec![] },
+        Game { id: 2, draws: vec![empty_draw.clone()] },
+        Game { id: 3, draws: vec![draw.clone(), empty_draw.clone(), draw.clone()] },
+        Game { id: 4, draws: vec![draw.clone()] },
+    ];
+    let result = games
+    .iter()
+    .map(|game| parse_game_line(&game.to_string()))
+    .collect::<Vec<Game>>();
+    println!("Test result: {:?} (expected: {:?})", result, games);
+
+    if result == games {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    for format in [Format::Text, Format::Json, Format::Csv] {
+        println!("SERIALIZATION: {:?} round-trip, edge cases", format);
+        let result = format.read(&format.write(&games)).expect("Could not read serialized games");
+
+        if result == games {
+            println!("Test successful!");
+        } else {
+            panic!("Test failed! {:?}", result);
+        }
+    }
+
+    println!("SERIALIZATION: CSV with split games or skipped draws");
+    let header = "game,draw,red,green,blue\n";
+    let result = [
+        format!("{}1,1,4,0,3\n2,1,1,0,0\n1,2,0,2,0\n", header),
+        format!("{}1,1,4,0,3\n1,3,1,0,0\n", header),
+        format!("{}1,1,4,0,3\n1,2,1,0,0\n", header),
+        // duplicated and unordered IDs are kept, for validation to report them
+        format!("{}2,1,4,0,3\n2,1,1,0,0\n1,0,0,0,0\n", header),
+    ]
+    .iter()
+    .map(|input| Format::Csv.read(input).map_err(|error| error.to_string()))
+    .map(|games| games.map(|games| games.len()))
+    .collect::<Vec<Result<usize, String>>>();
+    let expected = vec![
+        Err("Draw 2 of game 1 does not follow the row above (line 4)".to_string()),
+        Err("Draw 3 of game 1 does not follow the row above (line 3)".to_string()),
+        Ok(1),
+        Ok(3),
+    ];
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("SERIALIZATION: Text with invalid lines");
+    let result = ["Game x: 1 red", "Game 1: 1 red\n\nGame 2: 2 blue", "Game 1: many red"]
+    .iter()
+    .map(|input| Format::Text.read(input).map_err(|error| error.to_string()))
+    .map(|games| games.map(|games| games.len()))
+    .collect::<Vec<Result<usize, String>>>();
+    let expected = vec![
+        Err("Invalid game ID in \"Game x: 1 red\" (line 1)".to_string()),
+        Err("Empty line (line 2)".to_string()),
+        Err("Invalid red count in \"many red\" (line 1)".to_string()),
+    ];
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    for format in [Format::Text, Format::Json, Format::Csv] {
+        println!("SERIALIZATION: {:?} round-trip", format);
+
+        // the invalid file has duplicated, unordered and empty games
+        for file_path in [EXAMPLE_INPUT_PART_1, EXAMPLE_INPUT_INVALID, PUZZLE_INPUT] {
+            let games = parse_game_file(file_path);
+            let serialized = format.write(&games);
+            let deserialized = format.read(&serialized).expect("Could not read serialized games");
+
+            if deserialized == games {
+                println!("Test successful! ({}, {} games)", file_path, games.len());
+            } else {
+                panic!("Test failed! ({})", file_path);
+            }
+        }
+    }
+
+    println!("--- Bag inference ---");
+
+    println!("INFERENCE: Test file");
+    let games = parse_game_file(EXAMPLE_INPUT_PART_1);
+    let bag = create_threshold_map(12, 13, 14);
+
+    // labelling the games with the part 1 bag, it must be found back in the region
+    let (possible, impossible): (Vec<&Game>, Vec<&Game>) = games
+    .iter()
+    .partition(|game| is_game_possible(game, &bag));
+    let possible = possible.iter().map(|game| game.id).collect::<Vec<i32>>();
+    let impossible = impossible.iter().map(|game| game.id).collect::<Vec<i32>>();
+
+    let region = inference::infer_bag(&games, &possible, &impossible).unwrap();
+    let result = format_threshold_map(&region.minimal_bag);
+    let expected = "6 red, 3 green, 6 blue";
+    println!("Test result: {} (expected: {})", result, expected);
+
+    if result == expected && region.contains(&bag) && !region.contains(&create_threshold_map(20, 13, 15)) {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("INFERENCE: Test file, inconsistent labels");
+    let counterexample = match inference::infer_bag(&games, &[3], &[1]) {
+        Err(inference::InferenceError::Inconsistent(counterexample)) => counterexample,
+        other => panic!("Test failed! {:?}", other),
+    };
+    let result = counterexample.impossible_game;
+    let expected = 1;
+    println!("Test result: {} (expected: {})", result, expected);
+    println!("Forcing games: {:?}", counterexample.forcing_games);
+
+    if result == expected && counterexample.forcing_games["red"] == 3 {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("INFERENCE: Test file, unknown game IDs");
+    let result = match inference::infer_bag(&games, &[3, 7], &[1, 42]) {
+        Err(inference::InferenceError::UnknownGames(ids)) => ids,
+        other => panic!("Test failed! {:?}", other),
+    };
+    let expected = vec![7, 42];
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("INFERENCE: Input file");
+    let games = parse_game_file(PUZZLE_INPUT);
+    let (possible, impossible): (Vec<&Game>, Vec<&Game>) = games
+    .iter()
+    .partition(|game| is_game_possible(game, &bag));
+    let possible = possible.iter().map(|game| game.id).collect::<Vec<i32>>();
+    let impossible = impossible.iter().map(|game| game.id).collect::<Vec<i32>>();
+
+    let region = inference::infer_bag(&games, &possible, &impossible).unwrap();
+    println!("Minimal bag: {}", format_threshold_map(&region.minimal_bag));
+    println!("Excluded cube sets: {}", region.excluded.len());
+
+    println!("--- Statistics ---");
+
+    println!("STATISTICS: Draw probability");
+    let games = parse_game_file(EXAMPLE_INPUT_PART_1);
+    let bag = create_threshold_map(12, 13, 14);
+    let result = statistics::draw_probability(&games[0].draws[0], &bag);
+    let expected = 0.011714500878587567;
+    println!("Test result: {} (expected: {})", result, expected);
+
+    if (result - expected).abs() < 1e-12 {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("STATISTICS: Draw probabilities sum to one");
+    let small_bag = create_threshold_map(2, 1, 1);
+    let draws_of_two = [(2, 0, 0), (1, 1, 0), (1, 0, 1), (0, 1, 1)];
+    let result = draws_of_two
+    .iter()
+    .map(|(red, green, blue)| {
+        statistics::draw_probability(&Draw { red: *red, green: *green, blue: *blue }, &small_bag)
+    })
+    .sum::<f64>();
+    println!("Test result: {} (expected: 1)", result);
+
+    if (result - 1.0).abs() < 1e-12 {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("STATISTICS: Impossible game likelihood");
+    let result = statistics::game_likelihood(&games[2], &bag);
+    println!("Test result: {} (expected: 0)", result);
+
+    if result == 0.0 {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("STATISTICS: Maximum likelihood bag");
+    let red_only_games = parse_games("Game 1: 2 red; 2 red");
+    let (result, _) = statistics::maximum_likelihood_bag(&red_only_games, 2..=10, 1..=3, 1..=3).unwrap();
+    let result = format_threshold_map(&result);
+    let expected = "10 red, 1 green, 1 blue";
+    println!("Test result: {} (expected: {})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("STATISTICS: Input file");
+    let games = parse_game_file(PUZZLE_INPUT);
+    let possible_games = games
+    .into_iter()
+    .filter(|game| is_game_possible(game, &bag))
+    .collect::<Vec<Game>>();
+    let (result, log_likelihood) = statistics::maximum_likelihood_bag(&possible_games, 12..=20, 13..=20, 14..=20).unwrap();
+    println!("Maximum likelihood bag: {} (log-likelihood: {:.3})", format_threshold_map(&result), log_likelihood);
+
+    println!("--- Simulation ---");
+
+    println!("SIMULATION: Generating bag");
+    let simulation = simulation::Simulation {
+        bag: create_threshold_map(12, 13, 14),
+        draws_per_game: 4,
+        cubes_per_draw: 10,
+        seed: 2023,
+    };
+    let games = simulation.generate(100);
+
+    let result = simulation.validate(&games);
+    let expected = Ok((sum_possible_game_ids(&games, &simulation.bag), sum_powers(&games)));
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("SIMULATION: Seeded generation");
+    let result = Format::Text.write(&simulation.generate(100));
+    let expected = Format::Text.write(&games);
+
+    if result == expected && parse_games(&result) == games {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("SIMULATION: Smaller bag");
+    let smaller_bag = simulation::Simulation {
+        bag: create_threshold_map(3, 3, 3),
+        ..simulation
+    };
+
+    let result = smaller_bag.validate(&games).map_err(|error| error.split(" but ").next().unwrap().to_string());
+    let expected = Err("part 1 sum is 0".to_string());
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("SIMULATION: Games of another shape");
+    let more_draws = simulation::Simulation {
+        draws_per_game: 5,
+        ..simulation
+    };
+    let result = more_draws.validate(&games);
+    let expected = Err("game 1 has 4 draws instead of 5".to_string());
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("--- Batch evaluation ---");
+
+    println!("BATCH: Test file");
+    let games = parse_game_file(EXAMPLE_INPUT_PART_1);
+    let evaluator = batch::BatchEvaluator::new(&games);
+    let bags = [
+        create_threshold_map(12, 13, 14),
+        create_threshold_map(20, 13, 15),
+        create_threshold_map(6, 3, 6),
+        create_threshold_map(0, 0, 0),
+    ];
+    let result = evaluator.evaluate_all(&bags);
+    let expected = bags
+    .iter()
+    .map(|bag| part_1(EXAMPLE_INPUT_PART_1, bag.clone()))
+    .collect::<Vec<i32>>();
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("BATCH: Input file sweep");
+    let games = parse_game_file(PUZZLE_INPUT);
+    let sweep = batch::BatchEvaluator::new(&games).sweep(parse_range("8-16"), parse_range("9-17"), parse_range("12-14"));
+    let result = sweep.get(12, 13, 14).unwrap();
+    let expected = 2528;
+    println!("Test result: {} (expected: {})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("BATCH: Input file sweep, heatmap");
+    let heatmap = sweep.to_heatmap();
+    let result = heatmap.lines().take(3).collect::<Vec<&str>>();
+    let expected = vec!["blue = 12 (rows: green, columns: red 8..=16)", "   9 |   ......|", "  10 | .....:--|"];
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected && heatmap.lines().count() == 3 * 10 + 1 && sweep.get(17, 13, 14).is_none() {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("--- Query ---");
+
+    println!("QUERY: Test file, filter");
+    let games = parse_game_file(EXAMPLE_INPUT_PART_1);
+    let query = query::Query::parse("where max(red) > 10 or max(blue) >= 15 select id, power").unwrap();
+    let result = query.run(&games).rows;
+    let expected = vec![vec![3.0, 1560.0], vec![4.0, 630.0]];
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("QUERY: Test file, part 1 and part 2");
+    let query = query::Query::parse(
+        "select sum(id * (max(red) <= 12 and max(green) <= 13 and max(blue) <= 14)), sum(power), count()",
+    )
+    .unwrap();
+    let result = query.run(&games).rows;
+    let expected = vec![vec![8.0, 2286.0, 5.0]];
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("QUERY: Test file, grouping");
+    let query = query::Query::parse("group by draws select draws, count(), min(id), avg(max(red))").unwrap();
+    let result = query.run(&games).rows;
+    let expected = vec![vec![3.0, 4.0, 1.0, 9.75], vec![2.0, 1.0, 5.0, 6.0]];
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("QUERY: Invalid queries");
+    for invalid in ["select red", "group by draws select id", "where red > 1 select id", "select id +", "select sum(count())"] {
+        match query::Query::parse(invalid) {
+            Ok(_) => panic!("Test failed! ({})", invalid),
+            Err(error) => println!("Test successful! ({}: {})", invalid, error),
+        }
+    }
+
+    println!("QUERY: Input file");
+    let games = parse_game_file(PUZZLE_INPUT);
+    let query = query::Query::parse("where power > 1000 group by draws select draws, count(), avg(power), max(max(cubes))").unwrap();
+    print!("{}", query.run(&games));
+
+    println!("--- Validation ---");
+
+    for file_path in [EXAMPLE_INPUT_PART_1, EXAMPLE_INPUT_PART_2, PUZZLE_INPUT] {
+        println!("VALIDATION: {}", file_path);
+
+        match validation::load_validated_games(file_path, validation::ValidationMode::Strict) {
+            Ok(games) => println!("Test successful! ({} games)", games.len()),
+            Err(report) => panic!("Test failed!\n{}", report),
+        }
+    }
+
+    println!("VALIDATION: Invalid file, strict");
+    let report = validation::load_validated_games(EXAMPLE_INPUT_INVALID, validation::ValidationMode::Strict).unwrap_err();
+    print!("{}", report);
+    let result = report.issues.len();
+    let expected = 6;
+    println!("Test result: {} (expected: {})", result, expected);
+
+    if result == expected
+        && report.issues.contains(&validation::Issue::Duplicate { id: 2, lines: vec![2, 3] })
+        && report.issues.contains(&validation::Issue::Gap { missing: 3..=3 })
+        && report.issues.contains(&validation::Issue::OutOfOrder { id: 4, previous_id: 5, line: 5 })
+        && report.issues.contains(&validation::Issue::NonPositive { id: 0, line: 6 })
+        && report.issues.contains(&validation::Issue::NoDraws { id: 4, line: 5 })
+    {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("VALIDATION: Invalid file, lenient");
+    let result = validation::load_validated_games(EXAMPLE_INPUT_INVALID, validation::ValidationMode::Lenient)
+    .unwrap()
+    .len();
+    let expected = 6;
+    println!("Test result: {} (expected: {})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("--- Incremental evaluation ---");
+
+    println!("INCREMENTAL: Test file");
+    let mut evaluator = incremental::IncrementalEvaluator::new(create_threshold_map(12, 13, 14));
+
+    for line in read_to_string(EXAMPLE_INPUT_PART_1).unwrap().lines() {
+        evaluator.ingest_line(line);
+    }
+
+    let result = (evaluator.id_sum(), evaluator.power_sum());
+    let expected = (8, 2286);
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("INCREMENTAL: Correction and retraction");
+    // game 3 becomes possible, with a power of 0
+    evaluator.ingest_line("Game 3: 1 red");
+    // game 1 had a power of 48
+    evaluator.ingest_line("retract Game 1");
+
+    let result = (evaluator.id_sum(), evaluator.power_sum(), evaluator.game_count());
+    let expected = (8 + 3 - 1, 2286 - 1560 - 48, 4);
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("INCREMENTAL: Tailing input file");
+    let tailed_file = std::env::temp_dir().join(format!("day_2_tail_{}.txt", std::process::id()));
+    let tailed_path = tailed_file.to_str().unwrap();
+    let input = read_to_string(PUZZLE_INPUT).unwrap();
+    let mut evaluator = incremental::IncrementalEvaluator::new(create_threshold_map(12, 13, 14));
+
+    // appending the input in uneven chunks, cutting lines in the middle
+    let mut written = 0;
+    std::fs::write(tailed_path, "").unwrap();
+
+    while written < input.len() {
+        let chunk_end = (written + 777).min(input.len());
+
+        let mut file = std::fs::OpenOptions::new().append(true).open(tailed_path).unwrap();
+        std::io::Write::write_all(&mut file, &input.as_bytes()[written..chunk_end]).unwrap();
+        written = chunk_end;
+
+        evaluator.read_appended(tailed_path);
+    }
+
+    evaluator.flush();
+
+    let result = (evaluator.id_sum(), evaluator.power_sum());
+    let expected = (part_1(PUZZLE_INPUT, create_threshold_map(12, 13, 14)), part_2(PUZZLE_INPUT));
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("INCREMENTAL: Tailing a character cut in the middle");
+    let mut evaluator = incremental::IncrementalEvaluator::new(create_threshold_map(12, 13, 14));
+    let input = "Game 1: 3 red, \u{2713}\nGame 2: 20 blue\n".as_bytes();
+    // the check mark takes three bytes, the first read stops after the first one
+    std::fs::write(tailed_path, &input[..16]).unwrap();
+    let mut result = vec![evaluator.read_appended(tailed_path)];
+    std::fs::write(tailed_path, input).unwrap();
+    result.push(evaluator.read_appended(tailed_path));
+    let expected = vec![0, 2];
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected && evaluator.game_count() == 2 {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("INCREMENTAL: Tailing a truncated file, then a rotated one");
+    std::fs::write(tailed_path, "Game 3: 1 green\n").unwrap();
+    evaluator.read_appended(tailed_path);
+    let mut result = vec![(evaluator.id_sum(), evaluator.power_sum(), evaluator.game_count())];
+
+    // the new file is longer than what was read, only its identity tells it apart
+    let rotated_path = format!("{}.new", tailed_path);
+    std::fs::write(&rotated_path, "Game 4: 1 red, 2 green, 3 blue\nGame 5: 1 red\n").unwrap();
+    std::fs::rename(&rotated_path, tailed_path).unwrap();
+    evaluator.read_appended(tailed_path);
+    std::fs::remove_file(tailed_path).unwrap();
+    result.push((evaluator.id_sum(), evaluator.power_sum(), evaluator.game_count()));
+
+    let expected = vec![(3, 0, 1), (4 + 5, 6, 2)];
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("--- Explanation report ---");
+
+    println!("REPORT: Test file");
+    let games = parse_game_file(EXAMPLE_INPUT_PART_1);
+    let explanations = report::explain_games(&games, &create_threshold_map(12, 13, 14));
+    println!("{}", report::to_text(&explanations));
+    let result = explanations[2].to_string();
+    let expected = "Game 3: impossible, draw 1 has 20 red (8 over the limit of 12); minimal cube set: 20 red, 13 green, 6 blue (power 1560)";
+    println!("Test result: {} (expected: {})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("REPORT: Input file agrees with part 1 and part 2");
+    let games = parse_game_file(PUZZLE_INPUT);
+    let explanations = report::explain_games(&games, &create_threshold_map(12, 13, 14));
+    let result = (
+        explanations.iter().filter(|explanation| explanation.possible).map(|explanation| explanation.id).sum::<i32>(),
+        explanations.iter().map(|explanation| explanation.power).sum::<i32>(),
+    );
+    let expected = (part_1(PUZZLE_INPUT, create_threshold_map(12, 13, 14)), part_2(PUZZLE_INPUT));
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("REPORT: JSON output");
+    let json = report::to_json(&explanations);
+    let parsed = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+    let result = parsed.as_array().unwrap().len();
+    let expected = games.len();
+    println!("Test result: {} (expected: {})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("--- Sequence analytics ---");
+
+    println!("SEQUENCE: Color trends");
+    let games = parse_game_file(EXAMPLE_INPUT_PART_1);
+    let trends = sequence::color_trends(&games[0]);
+    for trend in &trends {
+        println!("{}: deltas {:?}, slope {:.2}, max at draw {:?}", trend.color, trend.deltas, trend.slope, trend.max_draw);
+    }
+    let result = (trends[0].deltas.clone(), trends[0].slope, trends.iter().map(|trend| trend.max_draw).collect::<Vec<Option<usize>>>());
+    let expected = (vec![-3, -1], -2.0, vec![Some(1), Some(2), Some(2)]);
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("SEQUENCE: Cubes per draw distribution");
+    let distribution = sequence::cubes_per_draw_distribution(&games);
+    println!("{:?}", distribution);
+    let result = distribution.values().sum::<usize>();
+    let expected = games.iter().map(|game| game.draws.len()).sum::<usize>();
+    println!("Test result: {} draws (expected: {})", result, expected);
+
+    if result == expected && distribution[&2] == 2 {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("SEQUENCE: Without replacement");
+    let bag = create_threshold_map(12, 13, 14);
+    let game = parse_game_line("Game 1: 5 red, 1 blue; 5 red, 13 blue; 5 red");
+    let result = sequence::without_replacement_violations(&game, &bag);
+    // the 14 blue cubes are all drawn but never more, only the red ones run out
+    let expected = vec![sequence::ExhaustedColor { draw: 3, color: "red".to_string(), drawn: 15, available: 12 }];
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("SEQUENCE: Input file, without replacement");
+    let games = parse_game_file(PUZZLE_INPUT);
+    let result = games
+    .iter()
+    .filter(|game| is_game_possible(game, &bag) && sequence::without_replacement_violations(game, &bag).is_empty())
+    .map(|game| game.id)
+    .sum::<i32>();
+    println!("Result: {}", result);
+
+    println!("--- Pareto frontier ---");
+
+    println!("PARETO: Test file");
+    let games = parse_game_file(EXAMPLE_INPUT_PART_1);
+    let frontier = pareto::pareto_minimal_cube_sets(&games);
+    for (id, cube_set) in &frontier {
+        println!("Game {}: {}", id, format_threshold_map(cube_set));
+    }
+    let result = frontier.iter().map(|(id, _)| *id).collect::<Vec<i32>>();
+    let expected = vec![1, 2, 5];
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("PARETO: Cheapest bags");
+    let cases = [
+        (5, pareto::Cost::TotalCubes, "20 red, 13 green, 15 blue", 48),
+        (1, pareto::Cost::TotalCubes, "1 red, 3 green, 4 blue", 8),
+        (2, pareto::Cost::TotalCubes, "4 red, 3 green, 6 blue", 13),
+        (2, pareto::Cost::MaxColor, "4 red, 3 green, 6 blue", 6),
+        (2, pareto::Cost::Weighted { red: 1, green: 1, blue: 10 }, "6 red, 3 green, 4 blue", 49),
+    ];
+
+    for (min_games, cost, expected_bag, expected_cost) in cases {
+        let (bag, bag_cost) = pareto::cheapest_bag(&games, min_games, cost).unwrap().unwrap();
+        let result = (format_threshold_map(&bag), bag_cost);
+        let expected = (expected_bag.to_string(), expected_cost);
+        println!("Test result: {:?} for {} games, {:?} (expected: {:?})", result, min_games, cost, expected);
+
+        if result == expected && sum_possible_game_ids(&games, &bag) > 0 {
+            println!("Test successful!");
+        } else {
+            panic!("Test failed!");
+        }
+    }
+
+    if pareto::cheapest_bag(&games, 6, pareto::Cost::TotalCubes).unwrap().is_some() {
+        panic!("Test failed!");
+    }
+
+    println!("PARETO: Test file, non-positive cube price");
+    let result = pareto::cheapest_bag(&games, 2, pareto::Cost::Weighted { red: 1, green: 0, blue: -2 });
+    let expected = Err("Cube prices must be positive, got 1 red, 0 green, -2 blue".to_string());
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("PARETO: Input file");
+    let games = parse_game_file(PUZZLE_INPUT);
+    println!("Pareto-minimal cube sets: {}", pareto::pareto_minimal_cube_sets(&games).len());
+
+    for min_games in [25, 50, 75, 100] {
+        let (bag, bag_cost) = pareto::cheapest_bag(&games, min_games, pareto::Cost::TotalCubes).unwrap().unwrap();
+        println!("Cheapest bag for {} games: {} ({} cubes)", min_games, format_threshold_map(&bag), bag_cost);
+    }
 }
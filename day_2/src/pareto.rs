@@ -0,0 +1,113 @@
+use crate::{covers, create_threshold_map, find_max_values, Game, ThresholdMap};
+
+// --- Pareto frontier ---
+// the games whose minimal cube set is not covered by the one of another game,
+// games with the same minimal cube set are all kept
+pub fn pareto_minimal_cube_sets(games: &[Game]) -> Vec<(i32, ThresholdMap)> {
+    let cube_sets = games
+    .iter()
+    .map(|game| (game.id, find_max_values(game)))
+    .collect::<Vec<(i32, ThresholdMap)>>();
+
+    cube_sets
+    .iter()
+    .filter(|(_, cube_set)| {
+        !cube_sets
+        .iter()
+        .any(|(_, other)| other != cube_set && covers(cube_set, other))
+    })
+    .cloned()
+    .collect()
+}
+
+// --- smallest bag ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cost {
+    // red + green + blue
+    TotalCubes,
+    // each color weighted by its own cube price
+    Weighted { red: i64, green: i64, blue: i64 },
+    // the largest of the three colors
+    MaxColor,
+}
+
+impl Cost {
+    pub fn of(&self, bag: &ThresholdMap) -> i64 {
+        let (red, green, blue) = (bag["red"] as i64, bag["green"] as i64, bag["blue"] as i64);
+
+        match self {
+            Cost::TotalCubes => red + green + blue,
+            Cost::Weighted { red: red_weight, green: green_weight, blue: blue_weight } => {
+                red * red_weight + green * green_weight + blue * blue_weight
+            }
+            Cost::MaxColor => red.max(green).max(blue),
+        }
+    }
+}
+
+// the cheapest bag making at least `min_games` games possible, none if there are not enough games,
+// ties are resolved in favor of the smallest red, then green count
+pub fn cheapest_bag(games: &[Game], min_games: usize, cost: Cost) -> Result<Option<(ThresholdMap, i64)>, String> {
+    // the search relies on the cost growing with each color
+    if let Cost::Weighted { red, green, blue } = cost {
+        if red <= 0 || green <= 0 || blue <= 0 {
+            return Err(format!("Cube prices must be positive, got {} red, {} green, {} blue", red, green, blue));
+        }
+    }
+
+    if min_games > games.len() {
+        return Ok(None);
+    }
+
+    if min_games == 0 {
+        let bag = create_threshold_map(0, 0, 0);
+        let bag_cost = cost.of(&bag);
+
+        return Ok(Some((bag, bag_cost)));
+    }
+
+    let cube_sets = games.iter().map(find_max_values).collect::<Vec<ThresholdMap>>();
+
+    // an optimal bag only ever needs as many cubes of a color as one of the games,
+    // since every cost function grows with each color
+    let candidates = |color: &str| {
+        let mut values = cube_sets.iter().map(|cube_set| cube_set[color]).collect::<Vec<i32>>();
+        values.push(0);
+        values.sort();
+        values.dedup();
+        values
+    };
+    let red_candidates = candidates("red");
+    let green_candidates = candidates("green");
+
+    let mut best: Option<(ThresholdMap, i64)> = None;
+
+    for &red in &red_candidates {
+        for &green in &green_candidates {
+            // with red and green fixed, the cheapest blue count is the one
+            // of the n-th least blue game among those fitting red and green
+            let mut blues = cube_sets
+            .iter()
+            .filter(|cube_set| cube_set["red"] <= red && cube_set["green"] <= green)
+            .map(|cube_set| cube_set["blue"])
+            .collect::<Vec<i32>>();
+
+            if blues.len() < min_games {
+                continue;
+            }
+
+            blues.sort();
+            let blue = blues[min_games - 1];
+
+            let bag = create_threshold_map(red, green, blue);
+            let bag_cost = cost.of(&bag);
+
+            match &best {
+                Some((_, best_cost)) if *best_cost <= bag_cost => (),
+                _ => best = Some((bag, bag_cost)),
+            }
+        }
+    }
+
+    Ok(best)
+}
@@ -0,0 +1,690 @@
+use std::fmt;
+
+use crate::{compute_power, find_max_values, Draw, Game};
+
+// --- query language ---
+// query := ["where" condition] ["group" "by" expression ("," expression)*] "select" expression ("," expression)*
+//
+// fields of a draw: red, green, blue, cubes (the total of the draw)
+// fields of a game: id, draws (the number of draws), power
+// aggregates: sum, min, max, avg, count
+//  - over the draws of a game when given a draw field, e.g. max(red)
+//  - over the games of a group otherwise, e.g. avg(power), avg(max(red)) or count()
+//
+// e.g. "where max(red) > 10 and draws >= 5 select id, power"
+//      "group by draws select draws, count(), avg(power)"
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    condition: Option<Expression>,
+    group_by: Vec<Expression>,
+    select: Vec<Expression>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expression {
+    Number(f64),
+    Field(Field),
+    Aggregate(Aggregate, Option<Box<Expression>>),
+    Binary(Box<Expression>, Operator, Box<Expression>),
+    Negate(Box<Expression>),
+    Not(Box<Expression>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Red,
+    Green,
+    Blue,
+    Cubes,
+    Id,
+    Draws,
+    Power,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Aggregate {
+    Sum,
+    Min,
+    Max,
+    Avg,
+    Count,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Greater,
+    GreaterOrEqual,
+    Lower,
+    LowerOrEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+// what an expression is evaluated on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Level {
+    Constant,
+    Draw,
+    Game,
+    Group,
+}
+
+// --- results ---
+#[derive(Debug)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<f64>>,
+}
+
+impl fmt::Display for QueryResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows = self.rows
+        .iter()
+        .map(|row| row.iter().map(|value| format_value(*value)).collect::<Vec<String>>())
+        .collect::<Vec<Vec<String>>>();
+
+        // every column is as wide as its widest cell
+        let widths = self.columns
+        .iter()
+        .enumerate()
+        .map(|(index, column)| rows.iter().map(|row| row[index].len()).fold(column.len(), usize::max))
+        .collect::<Vec<usize>>();
+
+        let header = self.columns
+        .iter()
+        .zip(&widths)
+        .map(|(column, width)| format!("{:<width$}", column, width = width))
+        .collect::<Vec<String>>();
+
+        writeln!(f, "{}", header.join(" | ").trim_end())?;
+        writeln!(f, "{}", widths.iter().map(|width| "-".repeat(*width)).collect::<Vec<String>>().join("-+-"))?;
+
+        for row in rows {
+            let cells = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:>width$}", cell, width = width))
+            .collect::<Vec<String>>();
+
+            writeln!(f, "{}", cells.join(" | "))?;
+        }
+
+        Ok(())
+    }
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_finite() && value.fract() == 0.0 {
+        format!("{}", value as i64)
+    } else {
+        format!("{:.2}", value)
+    }
+}
+
+// --- evaluation ---
+impl Query {
+    pub fn parse(input: &str) -> Result<Query, String> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { tokens, position: 0 };
+
+        let query = parser.parse_query()?;
+        query.check()?;
+
+        Ok(query)
+    }
+
+    // a query is grouped as soon as it has a "group by" or aggregates games
+    fn is_grouped(&self) -> bool {
+        !self.group_by.is_empty() || self.select.iter().any(|item| item.level() == Level::Group)
+    }
+
+    fn check(&self) -> Result<(), String> {
+        for expression in self.condition.iter().chain(&self.group_by).chain(&self.select) {
+            expression.validate()?;
+        }
+
+        if let Some(condition) = &self.condition {
+            if condition.level() > Level::Game || condition.level() == Level::Draw {
+                return Err(format!("Condition must apply to a game: {}", condition));
+            }
+        }
+
+        for key in &self.group_by {
+            if key.level() != Level::Game && key.level() != Level::Constant {
+                return Err(format!("Group key must apply to a game: {}", key));
+            }
+        }
+
+        for item in &self.select {
+            match item.level() {
+                Level::Draw => return Err(format!("Selected value must be aggregated: {}", item)),
+                Level::Game if self.is_grouped() && !self.group_by.contains(item) => {
+                    return Err(format!("Selected value must be aggregated or grouped by: {}", item))
+                }
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn run(&self, games: &[Game]) -> QueryResult {
+        let games = games
+        .iter()
+        .filter(|game| match &self.condition {
+            Some(condition) => condition.evaluate_game(game) != 0.0,
+            None => true,
+        })
+        .collect::<Vec<&Game>>();
+
+        let rows = if self.is_grouped() {
+            // groups are kept in order of first appearance
+            let mut groups: Vec<(Vec<f64>, Vec<&Game>)> = vec![];
+
+            for game in games {
+                let key = self.group_by.iter().map(|key| key.evaluate_game(game)).collect::<Vec<f64>>();
+
+                match groups.iter_mut().find(|(group_key, _)| *group_key == key) {
+                    Some((_, group)) => group.push(game),
+                    None => groups.push((key, vec![game])),
+                }
+            }
+
+            // aggregating without any key still gives a single row
+            if groups.is_empty() && self.group_by.is_empty() {
+                groups.push((vec![], vec![]));
+            }
+
+            groups
+            .iter()
+            .map(|(_, group)| self.select.iter().map(|item| item.evaluate_group(group)).collect())
+            .collect()
+        } else {
+            games
+            .iter()
+            .map(|game| self.select.iter().map(|item| item.evaluate_game(game)).collect())
+            .collect()
+        };
+
+        QueryResult {
+            columns: self.select.iter().map(|item| item.to_string()).collect(),
+            rows,
+        }
+    }
+}
+
+impl Expression {
+    // checks that draw values are only combined with draw values,
+    // and that group aggregates are not nested
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            Expression::Number(_) | Expression::Field(_) | Expression::Aggregate(_, None) => Ok(()),
+            Expression::Aggregate(_, Some(argument)) => {
+                argument.validate()?;
+
+                if argument.level() == Level::Group {
+                    return Err(format!("Aggregates over games can not be nested: {}", self));
+                }
+
+                Ok(())
+            }
+            Expression::Binary(left, _, right) => {
+                left.validate()?;
+                right.validate()?;
+
+                let levels = [left.level(), right.level()];
+
+                if levels.contains(&Level::Draw) && (levels.contains(&Level::Game) || levels.contains(&Level::Group)) {
+                    return Err(format!("Draw values must be aggregated before being combined: {}", self));
+                }
+
+                Ok(())
+            }
+            Expression::Negate(operand) | Expression::Not(operand) => operand.validate(),
+        }
+    }
+
+    fn level(&self) -> Level {
+        match self {
+            Expression::Number(_) => Level::Constant,
+            Expression::Field(Field::Red | Field::Green | Field::Blue | Field::Cubes) => Level::Draw,
+            Expression::Field(_) => Level::Game,
+            Expression::Aggregate(_, None) => Level::Group,
+            // an aggregate goes one level up from its argument
+            Expression::Aggregate(_, Some(argument)) => match argument.level() {
+                Level::Draw => Level::Game,
+                _ => Level::Group,
+            },
+            Expression::Binary(left, _, right) => left.level().max(right.level()),
+            Expression::Negate(operand) | Expression::Not(operand) => operand.level(),
+        }
+    }
+
+    fn evaluate_draw(&self, draw: &Draw) -> f64 {
+        match self {
+            Expression::Number(value) => *value,
+            Expression::Field(Field::Red) => draw.red as f64,
+            Expression::Field(Field::Green) => draw.green as f64,
+            Expression::Field(Field::Blue) => draw.blue as f64,
+            Expression::Field(Field::Cubes) => (draw.red + draw.green + draw.blue) as f64,
+            Expression::Binary(left, operator, right) => {
+                operator.apply(left.evaluate_draw(draw), right.evaluate_draw(draw))
+            }
+            Expression::Negate(operand) => -operand.evaluate_draw(draw),
+            Expression::Not(operand) => from_bool(operand.evaluate_draw(draw) == 0.0),
+            _ => unreachable!("{} can not be evaluated on a draw", self),
+        }
+    }
+
+    fn evaluate_game(&self, game: &Game) -> f64 {
+        match self {
+            Expression::Number(value) => *value,
+            Expression::Field(Field::Id) => game.id as f64,
+            Expression::Field(Field::Draws) => game.draws.len() as f64,
+            Expression::Field(Field::Power) => compute_power(&find_max_values(game)) as f64,
+            Expression::Aggregate(aggregate, Some(argument)) => {
+                let values = game.draws.iter().map(|draw| argument.evaluate_draw(draw)).collect::<Vec<f64>>();
+                aggregate.apply(&values)
+            }
+            Expression::Binary(left, operator, right) => {
+                operator.apply(left.evaluate_game(game), right.evaluate_game(game))
+            }
+            Expression::Negate(operand) => -operand.evaluate_game(game),
+            Expression::Not(operand) => from_bool(operand.evaluate_game(game) == 0.0),
+            _ => unreachable!("{} can not be evaluated on a game", self),
+        }
+    }
+
+    fn evaluate_group(&self, games: &[&Game]) -> f64 {
+        match self.level() {
+            // group keys are the same for every game of the group
+            Level::Constant | Level::Game => games.first().map_or(f64::NAN, |game| self.evaluate_game(game)),
+            _ => match self {
+                Expression::Aggregate(Aggregate::Count, None) => games.len() as f64,
+                Expression::Aggregate(aggregate, None) => unreachable!("{:?} needs an argument", aggregate),
+                Expression::Aggregate(aggregate, Some(argument)) => {
+                    let values = games.iter().map(|game| argument.evaluate_game(game)).collect::<Vec<f64>>();
+                    aggregate.apply(&values)
+                }
+                Expression::Binary(left, operator, right) => {
+                    operator.apply(left.evaluate_group(games), right.evaluate_group(games))
+                }
+                Expression::Negate(operand) => -operand.evaluate_group(games),
+                Expression::Not(operand) => from_bool(operand.evaluate_group(games) == 0.0),
+                _ => unreachable!("{} can not be evaluated on a group", self),
+            },
+        }
+    }
+}
+
+impl Aggregate {
+    fn apply(&self, values: &[f64]) -> f64 {
+        match self {
+            Aggregate::Sum => values.iter().sum(),
+            Aggregate::Min => values.iter().copied().reduce(f64::min).unwrap_or(f64::NAN),
+            Aggregate::Max => values.iter().copied().reduce(f64::max).unwrap_or(f64::NAN),
+            Aggregate::Avg => values.iter().sum::<f64>() / values.len() as f64,
+            // counting the values that are not zero, e.g. the draws with a red cube
+            Aggregate::Count => values.iter().filter(|value| **value != 0.0).count() as f64,
+        }
+    }
+}
+
+impl Operator {
+    fn apply(&self, left: f64, right: f64) -> f64 {
+        match self {
+            Operator::Or => from_bool(left != 0.0 || right != 0.0),
+            Operator::And => from_bool(left != 0.0 && right != 0.0),
+            Operator::Equal => from_bool(left == right),
+            Operator::NotEqual => from_bool(left != right),
+            Operator::Greater => from_bool(left > right),
+            Operator::GreaterOrEqual => from_bool(left >= right),
+            Operator::Lower => from_bool(left < right),
+            Operator::LowerOrEqual => from_bool(left <= right),
+            Operator::Add => left + right,
+            Operator::Subtract => left - right,
+            Operator::Multiply => left * right,
+            Operator::Divide => left / right,
+        }
+    }
+}
+
+fn from_bool(value: bool) -> f64 {
+    if value {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+// --- printing ---
+// expressions are printed back as they would be written, they are used as column names
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expression::Number(value) => write!(f, "{}", format_value(*value)),
+            Expression::Field(field) => write!(f, "{}", format!("{:?}", field).to_lowercase()),
+            Expression::Aggregate(aggregate, None) => write!(f, "{}()", format!("{:?}", aggregate).to_lowercase()),
+            Expression::Aggregate(aggregate, Some(argument)) => {
+                write!(f, "{}({})", format!("{:?}", aggregate).to_lowercase(), argument)
+            }
+            Expression::Binary(left, operator, right) => {
+                write!(f, "{} {} {}", Parenthesized(left), operator, Parenthesized(right))
+            }
+            Expression::Negate(operand) => write!(f, "-{}", Parenthesized(operand)),
+            Expression::Not(operand) => write!(f, "not {}", Parenthesized(operand)),
+        }
+    }
+}
+
+// nested operations are wrapped in parentheses so they read back the same way
+struct Parenthesized<'a>(&'a Expression);
+
+impl fmt::Display for Parenthesized<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Expression::Binary(..) | Expression::Not(_) => write!(f, "({})", self.0),
+            expression => write!(f, "{}", expression),
+        }
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Operator::Or => "or",
+            Operator::And => "and",
+            Operator::Equal => "=",
+            Operator::NotEqual => "!=",
+            Operator::Greater => ">",
+            Operator::GreaterOrEqual => ">=",
+            Operator::Lower => "<",
+            Operator::LowerOrEqual => "<=",
+            Operator::Add => "+",
+            Operator::Subtract => "-",
+            Operator::Multiply => "*",
+            Operator::Divide => "/",
+        };
+
+        write!(f, "{}", symbol)
+    }
+}
+
+// --- tokenizer ---
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Number(f64),
+    Symbol(&'static str),
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    const SYMBOLS: [&str; 14] = [">=", "<=", "!=", "==", ">", "<", "=", ",", "(", ")", "+", "-", "*", "/"];
+
+    let mut tokens = vec![];
+    let mut rest = input.trim_start();
+
+    while !rest.is_empty() {
+        let first_char = rest.chars().next().unwrap();
+
+        let length = if first_char.is_ascii_alphabetic() || first_char == '_' {
+            let length = rest.find(|c: char| !c.is_ascii_alphanumeric() && c != '_').unwrap_or(rest.len());
+            tokens.push(Token::Word(rest[..length].to_lowercase()));
+            length
+        } else if first_char.is_ascii_digit() {
+            let length = rest.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(rest.len());
+            let value = rest[..length].parse::<f64>().map_err(|_| format!("Invalid number: {}", &rest[..length]))?;
+            tokens.push(Token::Number(value));
+            length
+        } else if let Some(symbol) = SYMBOLS.iter().find(|symbol| rest.starts_with(**symbol)) {
+            tokens.push(Token::Symbol(symbol));
+            symbol.len()
+        } else {
+            return Err(format!("Unexpected character: {}", first_char));
+        };
+
+        rest = rest[length..].trim_start();
+    }
+
+    Ok(tokens)
+}
+
+// --- parser ---
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn accept_word(&mut self, word: &str) -> bool {
+        if self.peek() == Some(&Token::Word(word.to_string())) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn accept_symbol(&mut self, symbol: &str) -> bool {
+        match self.peek() {
+            Some(Token::Symbol(current)) if *current == symbol => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect_word(&mut self, word: &str) -> Result<(), String> {
+        if self.accept_word(word) {
+            Ok(())
+        } else {
+            Err(format!("Expected \"{}\", found {:?}", word, self.peek()))
+        }
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<(), String> {
+        if self.accept_symbol(symbol) {
+            Ok(())
+        } else {
+            Err(format!("Expected \"{}\", found {:?}", symbol, self.peek()))
+        }
+    }
+
+    fn parse_query(&mut self) -> Result<Query, String> {
+        let condition = if self.accept_word("where") {
+            Some(self.parse_or()?)
+        } else {
+            None
+        };
+
+        let group_by = if self.accept_word("group") {
+            self.expect_word("by")?;
+            self.parse_list()?
+        } else {
+            vec![]
+        };
+
+        self.expect_word("select")?;
+        let select = self.parse_list()?;
+
+        if let Some(token) = self.peek() {
+            return Err(format!("Unexpected token after query: {:?}", token));
+        }
+
+        Ok(Query { condition, group_by, select })
+    }
+
+    fn parse_list(&mut self) -> Result<Vec<Expression>, String> {
+        let mut expressions = vec![self.parse_or()?];
+
+        while self.accept_symbol(",") {
+            expressions.push(self.parse_or()?);
+        }
+
+        Ok(expressions)
+    }
+
+    fn parse_or(&mut self) -> Result<Expression, String> {
+        let mut left = self.parse_and()?;
+
+        while self.accept_word("or") {
+            let right = self.parse_and()?;
+            left = Expression::Binary(Box::new(left), Operator::Or, Box::new(right));
+        }
+
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expression, String> {
+        let mut left = self.parse_not()?;
+
+        while self.accept_word("and") {
+            let right = self.parse_not()?;
+            left = Expression::Binary(Box::new(left), Operator::And, Box::new(right));
+        }
+
+        Ok(left)
+    }
+
+    fn parse_not(&mut self) -> Result<Expression, String> {
+        if self.accept_word("not") {
+            Ok(Expression::Not(Box::new(self.parse_not()?)))
+        } else {
+            self.parse_comparison()
+        }
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expression, String> {
+        let left = self.parse_additive()?;
+
+        let operator = match self.peek() {
+            Some(Token::Symbol("=" | "==")) => Operator::Equal,
+            Some(Token::Symbol("!=")) => Operator::NotEqual,
+            Some(Token::Symbol(">")) => Operator::Greater,
+            Some(Token::Symbol(">=")) => Operator::GreaterOrEqual,
+            Some(Token::Symbol("<")) => Operator::Lower,
+            Some(Token::Symbol("<=")) => Operator::LowerOrEqual,
+            _ => return Ok(left),
+        };
+        self.position += 1;
+
+        let right = self.parse_additive()?;
+
+        Ok(Expression::Binary(Box::new(left), operator, Box::new(right)))
+    }
+
+    fn parse_additive(&mut self) -> Result<Expression, String> {
+        let mut left = self.parse_multiplicative()?;
+
+        loop {
+            let operator = if self.accept_symbol("+") {
+                Operator::Add
+            } else if self.accept_symbol("-") {
+                Operator::Subtract
+            } else {
+                return Ok(left);
+            };
+
+            let right = self.parse_multiplicative()?;
+            left = Expression::Binary(Box::new(left), operator, Box::new(right));
+        }
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Expression, String> {
+        let mut left = self.parse_unary()?;
+
+        loop {
+            let operator = if self.accept_symbol("*") {
+                Operator::Multiply
+            } else if self.accept_symbol("/") {
+                Operator::Divide
+            } else {
+                return Ok(left);
+            };
+
+            let right = self.parse_unary()?;
+            left = Expression::Binary(Box::new(left), operator, Box::new(right));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expression, String> {
+        if self.accept_symbol("-") {
+            Ok(Expression::Negate(Box::new(self.parse_unary()?)))
+        } else {
+            self.parse_primary()
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Expression, String> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(Expression::Number(value)),
+            Some(Token::Symbol("(")) => {
+                let expression = self.parse_or()?;
+                self.expect_symbol(")")?;
+                Ok(expression)
+            }
+            Some(Token::Word(word)) => {
+                let aggregate = match word.as_str() {
+                    "sum" => Some(Aggregate::Sum),
+                    "min" => Some(Aggregate::Min),
+                    "max" => Some(Aggregate::Max),
+                    "avg" => Some(Aggregate::Avg),
+                    "count" => Some(Aggregate::Count),
+                    _ => None,
+                };
+
+                if let Some(aggregate) = aggregate {
+                    self.expect_symbol("(")?;
+
+                    // count() and count(*) count the games of the group
+                    if aggregate == Aggregate::Count {
+                        if self.accept_symbol(")") {
+                            return Ok(Expression::Aggregate(aggregate, None));
+                        }
+
+                        if self.accept_symbol("*") {
+                            self.expect_symbol(")")?;
+                            return Ok(Expression::Aggregate(aggregate, None));
+                        }
+                    }
+
+                    let argument = self.parse_or()?;
+                    self.expect_symbol(")")?;
+
+                    return Ok(Expression::Aggregate(aggregate, Some(Box::new(argument))));
+                }
+
+                let field = match word.as_str() {
+                    "red" => Field::Red,
+                    "green" => Field::Green,
+                    "blue" => Field::Blue,
+                    "cubes" => Field::Cubes,
+                    "id" => Field::Id,
+                    "draws" => Field::Draws,
+                    "power" => Field::Power,
+                    _ => return Err(format!("Unknown field: {}", word)),
+                };
+
+                Ok(Expression::Field(field))
+            }
+            token => Err(format!("Unexpected token: {:?}", token)),
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::{
+    compute_power, create_color_vec, create_threshold_map, find_max_values, format_threshold_map, Game, ThresholdMap,
+};
+
+// --- explanation report ---
+// why each game is, or is not, part of the part 1 sum
+#[derive(Debug, Serialize)]
+pub struct GameExplanation {
+    pub id: i32,
+    pub possible: bool,
+    // the first draw going over the threshold, with every color over it in that draw
+    pub first_violation: Option<Violation>,
+    pub minimal_cube_set: CubeSet,
+    pub power: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Violation {
+    // draws are counted from 1, as they are read in the file
+    pub draw: usize,
+    pub colors: Vec<ColorExcess>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ColorExcess {
+    pub color: String,
+    pub count: i32,
+    pub threshold: i32,
+    pub excess: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CubeSet {
+    pub red: i32,
+    pub green: i32,
+    pub blue: i32,
+}
+
+pub fn explain_games(games: &[Game], threshold_map: &ThresholdMap) -> Vec<GameExplanation> {
+    games
+    .iter()
+    .map(|game| explain_game(game, threshold_map))
+    .collect()
+}
+
+pub fn explain_game(game: &Game, threshold_map: &ThresholdMap) -> GameExplanation {
+    let first_violation = game.draws
+    .iter()
+    .enumerate()
+    .find_map(|(index, draw)| {
+        let counts = [draw.red, draw.green, draw.blue];
+
+        let colors = create_color_vec()
+        .iter()
+        .zip(counts)
+        .filter(|(color, count)| *count > threshold_map[**color])
+        .map(|(color, count)| ColorExcess {
+            color: color.to_string(),
+            count,
+            threshold: threshold_map[*color],
+            excess: count - threshold_map[*color],
+        })
+        .collect::<Vec<ColorExcess>>();
+
+        if colors.is_empty() {
+            None
+        } else {
+            Some(Violation { draw: index + 1, colors })
+        }
+    });
+
+    let max_values = find_max_values(game);
+
+    GameExplanation {
+        id: game.id,
+        possible: first_violation.is_none(),
+        first_violation,
+        minimal_cube_set: CubeSet {
+            red: max_values["red"],
+            green: max_values["green"],
+            blue: max_values["blue"],
+        },
+        power: compute_power(&max_values),
+    }
+}
+
+// --- output ---
+impl fmt::Display for GameExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Game {}: ", self.id)?;
+
+        match &self.first_violation {
+            None => write!(f, "possible")?,
+            Some(violation) => {
+                let colors = violation.colors
+                .iter()
+                .map(|excess| {
+                    format!("{} {} ({} over the limit of {})", excess.count, excess.color, excess.excess, excess.threshold)
+                })
+                .collect::<Vec<String>>();
+
+                write!(f, "impossible, draw {} has {}", violation.draw, colors.join(", "))?;
+            }
+        }
+
+        let cube_set = create_threshold_map(
+            self.minimal_cube_set.red,
+            self.minimal_cube_set.green,
+            self.minimal_cube_set.blue,
+        );
+
+        write!(f, "; minimal cube set: {} (power {})", format_threshold_map(&cube_set), self.power)
+    }
+}
+
+pub fn to_text(explanations: &[GameExplanation]) -> String {
+    explanations
+    .iter()
+    .map(|explanation| explanation.to_string())
+    .collect::<Vec<String>>()
+    .join("\n")
+}
+
+pub fn to_json(explanations: &[GameExplanation]) -> String {
+    serde_json::to_string_pretty(explanations).expect("Could not serialize report")
+}
@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+
+use crate::{create_color_vec, Draw, Game, ThresholdMap};
+
+// --- sequence analytics ---
+// draws are looked at in the order they appear in the game
+#[derive(Debug)]
+pub struct ColorTrend {
+    pub color: String,
+    // difference with the previous draw, one less than there are draws
+    pub deltas: Vec<i32>,
+    // slope of the least squares line through (draw, count), 0 with less than 2 draws
+    pub slope: f64,
+    // the first draw reaching the color maximum, counted from 1,
+    // none if the color is never drawn
+    pub max_draw: Option<usize>,
+}
+
+pub fn color_trends(game: &Game) -> Vec<ColorTrend> {
+    create_color_vec()
+    .iter()
+    .map(|color| {
+        let counts = game.draws.iter().map(|draw| color_count(draw, color)).collect::<Vec<i32>>();
+
+        let deltas = counts.windows(2).map(|pair| pair[1] - pair[0]).collect();
+
+        let max = counts.iter().copied().max().unwrap_or(0);
+        let max_draw = if max > 0 {
+            counts.iter().position(|count| *count == max).map(|index| index + 1)
+        } else {
+            None
+        };
+
+        ColorTrend {
+            color: color.to_string(),
+            deltas,
+            slope: slope(&counts),
+            max_draw,
+        }
+    })
+    .collect()
+}
+
+// number of draws for each number of cubes per draw, across all the games
+pub fn cubes_per_draw_distribution(games: &[Game]) -> BTreeMap<i32, usize> {
+    let mut distribution = BTreeMap::new();
+
+    for draw in games.iter().flat_map(|game| &game.draws) {
+        *distribution.entry(draw.red + draw.green + draw.blue).or_insert(0) += 1;
+    }
+
+    distribution
+}
+
+// --- without replacement variant ---
+// cubes are never put back in the bag during a game, so a draw is impossible
+// as soon as it takes more cubes of a color than the earlier draws left
+#[derive(Debug, PartialEq, Eq)]
+pub struct ExhaustedColor {
+    // counted from 1
+    pub draw: usize,
+    pub color: String,
+    // cubes of the color drawn up to and including this draw
+    pub drawn: i32,
+    pub available: i32,
+}
+
+pub fn without_replacement_violations(game: &Game, bag: &ThresholdMap) -> Vec<ExhaustedColor> {
+    let colors = create_color_vec();
+
+    let mut violations = vec![];
+    let mut drawn = [0; 3];
+
+    for (index, draw) in game.draws.iter().enumerate() {
+        for (color_index, color) in colors.iter().enumerate() {
+            let previously_drawn = drawn[color_index];
+            drawn[color_index] += color_count(draw, color);
+
+            // only the draw that goes over the bag is reported for a color
+            if drawn[color_index] > bag[*color] && previously_drawn <= bag[*color] {
+                violations.push(ExhaustedColor {
+                    draw: index + 1,
+                    color: color.to_string(),
+                    drawn: drawn[color_index],
+                    available: bag[*color],
+                });
+            }
+        }
+    }
+
+    violations
+}
+
+// --- helpers ---
+fn color_count(draw: &Draw, color: &str) -> i32 {
+    match color {
+        "red" => draw.red,
+        "green" => draw.green,
+        "blue" => draw.blue,
+        _ => 0,
+    }
+}
+
+fn slope(counts: &[i32]) -> f64 {
+    if counts.len() < 2 {
+        return 0.0;
+    }
+
+    // draws are numbered from 1
+    let n = counts.len() as f64;
+    let mean_x = (n + 1.0) / 2.0;
+    let mean_y = counts.iter().sum::<i32>() as f64 / n;
+
+    let (covariance, variance) = counts
+    .iter()
+    .enumerate()
+    .map(|(index, count)| ((index + 1) as f64 - mean_x, *count as f64 - mean_y))
+    .fold((0.0, 0.0), |(covariance, variance), (dx, dy)| (covariance + dx * dy, variance + dx * dx));
+
+    covariance / variance
+}
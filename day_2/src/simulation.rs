@@ -0,0 +1,128 @@
+use rand::{seq::SliceRandom, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::{
+    compute_power, create_color_vec, find_max_values, format_threshold_map, sum_possible_game_ids, sum_powers, Draw,
+    Game, ThresholdMap,
+};
+
+// --- simulation ---
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    // colors and counts of the cubes in the bag, colors other than
+    // red, green and blue are drawn but not recorded in the games
+    pub bag: ThresholdMap,
+    pub draws_per_game: usize,
+    pub cubes_per_draw: usize,
+    // the same seed always generates the same games
+    pub seed: u64,
+}
+
+impl Simulation {
+    pub fn generate(&self, game_count: usize) -> Vec<Game> {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+
+        // one entry per cube in the bag, sorted so that the seed does not
+        // depend on the iteration order of the map
+        let mut colors = self.bag.keys().collect::<Vec<&String>>();
+        colors.sort();
+
+        let cubes = colors
+        .iter()
+        .flat_map(|color| std::iter::repeat_n(color.as_str(), self.bag[*color].max(0) as usize))
+        .collect::<Vec<&str>>();
+
+        if self.cubes_per_draw > cubes.len() {
+            panic!("Can not draw {} cubes from a bag of {}", self.cubes_per_draw, cubes.len());
+        }
+
+        (1..=game_count)
+        .map(|id| Game {
+            id: id as i32,
+            draws: (0..self.draws_per_game)
+            .map(|_| {
+                // cubes of a draw are taken without replacement,
+                // then the whole bag is used again for the next draw
+                let mut draw = Draw::default();
+
+                for cube in cubes.choose_multiple(&mut rng, self.cubes_per_draw) {
+                    match *cube {
+                        "red" => draw.red += 1,
+                        "green" => draw.green += 1,
+                        "blue" => draw.blue += 1,
+                        _ => (),
+                    }
+                }
+
+                draw
+            })
+            .collect(),
+        })
+        .collect()
+    }
+
+    // games drawn from the bag must all be possible with it, none of them can
+    // have a power above the one of the bag, and they must have the requested shape,
+    // returns the part 1 and part 2 answers of the games
+    pub fn validate(&self, games: &[Game]) -> Result<(i32, i32), String> {
+        let mut bag = self.bag.clone();
+
+        for color in create_color_vec() {
+            bag.entry(color.to_string()).or_insert(0);
+        }
+
+        let id_sum = sum_possible_game_ids(games, &bag);
+        let expected_id_sum = games.iter().map(|game| game.id).sum::<i32>();
+
+        if id_sum != expected_id_sum {
+            return Err(format!(
+                "part 1 sum is {} but every game should be possible (expected: {})",
+                id_sum, expected_id_sum
+            ));
+        }
+
+        for game in games {
+            if game.draws.len() != self.draws_per_game {
+                return Err(format!(
+                    "game {} has {} draws instead of {}",
+                    game.id,
+                    game.draws.len(),
+                    self.draws_per_game
+                ));
+            }
+
+            // cubes of other colors are not recorded, so a draw can hold fewer
+            if let Some(draw) = game
+            .draws
+            .iter()
+            .find(|draw| (draw.red + draw.green + draw.blue) as usize > self.cubes_per_draw)
+            {
+                return Err(format!(
+                    "game {} has a draw of {} cubes but only {} are drawn at a time",
+                    game.id,
+                    draw.red + draw.green + draw.blue,
+                    self.cubes_per_draw
+                ));
+            }
+        }
+
+        // powers are compared as i64, the power of a large bag does not fit in an i32
+        let bag_power = bag["red"] as i64 * bag["green"] as i64 * bag["blue"] as i64;
+
+        for game in games {
+            let max_values = find_max_values(game);
+
+            if compute_power(&max_values) as i64 > bag_power {
+                return Err(format!(
+                    "game {} has a power of {} ({}) but the bag only has {}",
+                    game.id,
+                    compute_power(&max_values),
+                    format_threshold_map(&max_values),
+                    bag_power
+                ));
+            }
+        }
+
+        Ok((id_sum, sum_powers(games)))
+    }
+}
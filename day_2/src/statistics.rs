@@ -0,0 +1,96 @@
+use std::ops::RangeInclusive;
+
+use crate::{create_threshold_map, Draw, Game, ThresholdMap};
+
+// --- likelihood ---
+// cubes are put back in the bag between draws, but not while drawing,
+// so a draw follows a multivariate hypergeometric distribution given its size
+pub fn draw_probability(draw: &Draw, bag: &ThresholdMap) -> f64 {
+    draw_log_probability(draw, bag).exp()
+}
+
+pub fn draw_log_probability(draw: &Draw, bag: &ThresholdMap) -> f64 {
+    let bag_red = bag["red"];
+    let bag_green = bag["green"];
+    let bag_blue = bag["blue"];
+
+    // a draw that does not fit in the bag can not happen
+    if draw.red < 0 || draw.green < 0 || draw.blue < 0
+        || draw.red > bag_red || draw.green > bag_green || draw.blue > bag_blue
+    {
+        return f64::NEG_INFINITY;
+    }
+
+    let drawn = draw.red + draw.green + draw.blue;
+    let total = bag_red + bag_green + bag_blue;
+
+    ln_choose(bag_red, draw.red)
+        + ln_choose(bag_green, draw.green)
+        + ln_choose(bag_blue, draw.blue)
+        - ln_choose(total, drawn)
+}
+
+// draws are independent from each other, so the game likelihood is their product
+pub fn game_likelihood(game: &Game, bag: &ThresholdMap) -> f64 {
+    game_log_likelihood(game, bag).exp()
+}
+
+pub fn game_log_likelihood(game: &Game, bag: &ThresholdMap) -> f64 {
+    game.draws
+    .iter()
+    .map(|draw| draw_log_probability(draw, bag))
+    .sum()
+}
+
+// --- estimation ---
+// the bag of the search space under which the games are the most likely,
+// ties are resolved in favor of the first bag found
+pub fn maximum_likelihood_bag(
+    games: &[Game],
+    red: RangeInclusive<i32>,
+    green: RangeInclusive<i32>,
+    blue: RangeInclusive<i32>,
+) -> Option<(ThresholdMap, f64)> {
+    let mut best: Option<(ThresholdMap, f64)> = None;
+
+    for red in red {
+        for green in green.clone() {
+            for blue in blue.clone() {
+                let bag = create_threshold_map(red, green, blue);
+
+                let log_likelihood = games
+                .iter()
+                .map(|game| game_log_likelihood(game, &bag))
+                .sum::<f64>();
+
+                // skipping bags that can not produce the games at all
+                if log_likelihood == f64::NEG_INFINITY {
+                    continue;
+                }
+
+                match &best {
+                    Some((_, best_log_likelihood)) if *best_log_likelihood >= log_likelihood => (),
+                    _ => best = Some((bag, log_likelihood)),
+                }
+            }
+        }
+    }
+
+    best
+}
+
+// --- helpers ---
+// natural logarithm of the binomial coefficient, kept in log space
+// so that large bags do not overflow
+fn ln_choose(n: i32, k: i32) -> f64 {
+    if k < 0 || k > n {
+        return f64::NEG_INFINITY;
+    }
+
+    // C(n, k) == C(n, n - k), the smaller one needs less terms
+    let k = k.min(n - k);
+
+    (0..k)
+    .map(|i| ((n - i) as f64).ln() - ((i + 1) as f64).ln())
+    .sum()
+}
@@ -0,0 +1,140 @@
+use std::{fmt, fs::read_to_string, ops::RangeInclusive};
+
+use crate::{parse_games, Game};
+
+// --- validation ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationMode {
+    // any issue rejects the whole file
+    Strict,
+    // issues are printed as warnings and the games are kept as they are
+    Lenient,
+}
+
+// lines are counted from 1, one game per line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    Duplicate { id: i32, lines: Vec<usize> },
+    Gap { missing: RangeInclusive<i32> },
+    OutOfOrder { id: i32, previous_id: i32, line: usize },
+    NonPositive { id: i32, line: usize },
+    NoDraws { id: i32, line: usize },
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::Duplicate { id, lines } => {
+                let lines = lines.iter().map(|line| line.to_string()).collect::<Vec<String>>();
+                write!(f, "game {} is defined more than once (lines {})", id, lines.join(", "))
+            }
+            Issue::Gap { missing } if missing.start() == missing.end() => write!(f, "game {} is missing", missing.start()),
+            Issue::Gap { missing } => write!(f, "games {} to {} are missing", missing.start(), missing.end()),
+            Issue::OutOfOrder { id, previous_id, line } => {
+                write!(f, "game {} comes after game {} (line {})", id, previous_id, line)
+            }
+            Issue::NonPositive { id, line } => write!(f, "game {} has an ID lower than 1 (line {})", id, line),
+            Issue::NoDraws { id, line } => write!(f, "game {} has no draws (line {})", id, line),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    pub issues: Vec<Issue>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for issue in &self.issues {
+            writeln!(f, "{}", issue)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub fn validate_games(games: &[Game]) -> ValidationReport {
+    let mut issues = vec![];
+
+    // IDs and draws, in file order
+    for (index, game) in games.iter().enumerate() {
+        let line = index + 1;
+
+        if game.id < 1 {
+            issues.push(Issue::NonPositive { id: game.id, line });
+        }
+
+        if index > 0 && game.id < games[index - 1].id {
+            issues.push(Issue::OutOfOrder {
+                id: game.id,
+                previous_id: games[index - 1].id,
+                line,
+            });
+        }
+
+        // "Game 1:" has no draws, and draws with no cubes at all count as none
+        if game.draws.iter().all(|draw| draw.red == 0 && draw.green == 0 && draw.blue == 0) {
+            issues.push(Issue::NoDraws { id: game.id, line });
+        }
+    }
+
+    // duplicates, reported once per ID
+    let mut ids = games.iter().map(|game| game.id).collect::<Vec<i32>>();
+    ids.sort();
+    ids.dedup();
+
+    for id in &ids {
+        let lines = games
+        .iter()
+        .enumerate()
+        .filter(|(_, game)| game.id == *id)
+        .map(|(index, _)| index + 1)
+        .collect::<Vec<usize>>();
+
+        if lines.len() > 1 {
+            issues.push(Issue::Duplicate { id: *id, lines });
+        }
+    }
+
+    // gaps, IDs are expected to go from 1 to the highest one
+    let mut expected = 1;
+
+    for id in ids.into_iter().filter(|id| *id >= 1) {
+        if id > expected {
+            issues.push(Issue::Gap { missing: expected..=id - 1 });
+        }
+
+        expected = id + 1;
+    }
+
+    ValidationReport { issues }
+}
+
+pub fn load_validated_games(file_path: &str, mode: ValidationMode) -> Result<Vec<Game>, ValidationReport> {
+    let lines = read_to_string(file_path).expect("Could not read file");
+    let games = parse_games(&lines);
+
+    let report = validate_games(&games);
+
+    if report.is_valid() {
+        return Ok(games);
+    }
+
+    match mode {
+        ValidationMode::Strict => Err(report),
+        ValidationMode::Lenient => {
+            for issue in &report.issues {
+                eprintln!("warning: {}", issue);
+            }
+
+            Ok(games)
+        }
+    }
+}
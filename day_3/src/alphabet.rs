@@ -0,0 +1,129 @@
+use std::fmt;
+
+use crate::{
+    grid::{Grid, RaggedRow},
+    Position,
+};
+
+// --- alphabet ---
+// what each character of a schematic stands for, digits are always digits
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alphabet {
+    pub blanks: Vec<char>,
+    // the symbols, any character that is neither a digit nor a blank when empty
+    pub symbols: Vec<char>,
+    // read as blanks, without being reported
+    pub ignored: Vec<char>,
+    // characters that should never be found in a schematic, the file is refused
+    pub rejected: Vec<char>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharClass {
+    Digit,
+    Blank,
+    Symbol,
+    // none of the above, read as a blank and reported
+    Unexpected,
+    Rejected,
+}
+
+impl Alphabet {
+    pub fn new(blanks: &str, symbols: &str, ignored: &str, rejected: &str) -> Self {
+        Alphabet {
+            blanks: blanks.chars().collect(),
+            symbols: symbols.chars().collect(),
+            ignored: ignored.chars().collect(),
+            rejected: rejected.chars().collect(),
+        }
+    }
+
+    pub fn classify(&self, ch: char) -> CharClass {
+        if self.rejected.contains(&ch) {
+            CharClass::Rejected
+        } else if ch.is_ascii_digit() {
+            CharClass::Digit
+        } else if self.blanks.contains(&ch) || self.ignored.contains(&ch) {
+            CharClass::Blank
+        } else if self.symbols.is_empty() || self.symbols.contains(&ch) {
+            CharClass::Symbol
+        } else {
+            CharClass::Unexpected
+        }
+    }
+
+    pub fn is_symbol(&self, ch: char) -> bool {
+        self.classify(ch) == CharClass::Symbol
+    }
+}
+
+// the puzzle one: anything that is neither a digit nor a dot is a symbol
+impl Default for Alphabet {
+    fn default() -> Self {
+        Alphabet::new(".", "", "", "")
+    }
+}
+
+// --- validation ---
+// positions are (row, column), both counted from 1 when displayed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    Unexpected { ch: char, pos: Position },
+    Rejected { ch: char, pos: Position },
+    Ragged(RaggedRow),
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::Unexpected { ch, pos } => {
+                write!(f, "unexpected character {:?} at row {}, column {}", ch, pos.0 + 1, pos.1 + 1)
+            }
+            Issue::Rejected { ch, pos } => {
+                write!(f, "rejected character {:?} at row {}, column {}", ch, pos.0 + 1, pos.1 + 1)
+            }
+            Issue::Ragged(ragged) => {
+                write!(f, "row {} has {} columns instead of {}", ragged.row + 1, ragged.width, ragged.expected)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    pub issues: Vec<Issue>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    // unexpected characters can still be read, rejected ones and ragged rows cannot
+    pub fn is_rejected(&self) -> bool {
+        self.issues.iter().any(|issue| !matches!(issue, Issue::Unexpected { .. }))
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for issue in &self.issues {
+            writeln!(f, "{}", issue)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub fn validate(grid: &Grid<char>, alphabet: &Alphabet) -> ValidationReport {
+    let issues = grid
+    .iter()
+    .filter_map(|(pos, ch)| match alphabet.classify(*ch) {
+        CharClass::Unexpected => Some(Issue::Unexpected { ch: *ch, pos }),
+        CharClass::Rejected => Some(Issue::Rejected { ch: *ch, pos }),
+        _ => None,
+    })
+    .collect::<Vec<Issue>>();
+
+    ValidationReport { issues }
+}
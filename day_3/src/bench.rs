@@ -0,0 +1,143 @@
+use std::{collections::HashMap, time::Instant};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use regex::Regex;
+
+use crate::{grid::Grid, sum_gear_ratios, sum_part_numbers, CountingMode, Position};
+
+const SYMBOLS: [char; 10] = ['#', '$', '%', '&', '*', '+', '-', '/', '=', '@'];
+
+// --- generation ---
+// a random schematic looking like the puzzle input: about one cell in ten starts
+// a number of 1 to 3 digits and one in twenty is a symbol, a third of them gears
+pub fn generate_schematic(rows: usize, cols: usize, seed: u64) -> String {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut lines: Vec<String> = vec![];
+
+    for _ in 0..rows {
+        let mut line = String::new();
+
+        while line.len() < cols {
+            let roll = rng.gen_range(0..100);
+
+            if roll < 10 {
+                let digits = rng.gen_range(1..=3).min(cols - line.len());
+                let number = rng.gen_range(10u32.pow(digits as u32 - 1)..10u32.pow(digits as u32));
+                line.push_str(&number.to_string());
+
+                // numbers next to each other would merge, so a number is always followed by a dot
+                if line.len() < cols {
+                    line.push('.');
+                }
+            } else if roll < 12 {
+                line.push('*');
+            } else if roll < 15 {
+                line.push(SYMBOLS[rng.gen_range(0..SYMBOLS.len())]);
+            } else {
+                line.push('.');
+            }
+        }
+
+        lines.push(line);
+    }
+
+    lines.join("\n")
+}
+
+// --- reference ---
+// the original scan, comparing every number with every symbol,
+// kept to check the index against it and to compare their scaling,
+// numbers are counted once per adjacent symbol as it did
+pub fn naive_sums(input: &str) -> (i64, i64) {
+    let grid = Grid::parse(input);
+    let re = Regex::new(r"\d+").unwrap();
+
+    let numbers = input
+    .lines()
+    .enumerate()
+    .flat_map(|(row, line)| {
+        re.find_iter(line)
+        .map(move |m| (m.as_str().parse::<i64>().unwrap(), Position(row as i32, m.start() as i32), m.len()))
+        .collect::<Vec<(i64, Position, usize)>>()
+    })
+    .collect::<Vec<(i64, Position, usize)>>();
+
+    let symbols = grid
+    .iter()
+    .filter(|(_, ch)| !ch.is_ascii_digit() && **ch != '.')
+    .map(|(position, ch)| (*ch, position))
+    .collect::<Vec<(char, Position)>>();
+
+    let mut part_numbers: Vec<i64> = vec![];
+    let mut gear_table: HashMap<Position, Vec<i64>> = HashMap::new();
+
+    for (value, start, len) in &numbers {
+        for (ch, symbol_pos) in &symbols {
+            let touches = grid
+            .neighbors8(*symbol_pos)
+            .any(|neighbor| neighbor.0 == start.0 && neighbor.1 >= start.1 && neighbor.1 < start.1 + *len as i32);
+
+            if touches {
+                part_numbers.push(*value);
+
+                if *ch == '*' {
+                    gear_table.entry(*symbol_pos).or_default().push(*value);
+                }
+            }
+        }
+    }
+
+    let gear_ratios = gear_table
+    .values()
+    .filter(|values| values.len() == 2)
+    .map(|values| values.iter().product::<i64>())
+    .sum();
+
+    (part_numbers.iter().sum(), gear_ratios)
+}
+
+// --- benchmark ---
+// times both parts on square schematics doubling in size, with the index
+// the time per cell should stay about the same while the scan one keeps growing
+pub fn run() {
+    println!("{:>6} | {:>9} | {:>12} | {:>12} | {:>12} | {:>12}", "size", "cells", "index (ms)", "index ns/cell", "scan (ms)", "scan ns/cell");
+
+    for size in [32, 64, 128, 256, 512, 1024, 2048] {
+        let schematic = generate_schematic(size, size, size as u64);
+        let cells = (size * size) as f64;
+
+        let start = Instant::now();
+        let indexed = (sum_part_numbers(&schematic, CountingMode::PerAdjacentSymbol), sum_gear_ratios(&schematic));
+        let index_time = start.elapsed().as_secs_f64();
+
+        // the scan gets too slow on the largest schematics
+        let scan_time = if size <= 256 {
+            let start = Instant::now();
+            let scanned = naive_sums(&schematic);
+            let scan_time = start.elapsed().as_secs_f64();
+
+            if scanned != indexed {
+                panic!("Index and scan disagree on a {}x{} schematic: {:?} != {:?}", size, size, indexed, scanned);
+            }
+
+            Some(scan_time)
+        } else {
+            None
+        };
+
+        let scan_columns = match scan_time {
+            Some(scan_time) => format!("{:>12.3} | {:>12.1}", scan_time * 1e3, scan_time * 1e9 / cells),
+            None => format!("{:>12} | {:>12}", "-", "-"),
+        };
+
+        println!(
+            "{:>6} | {:>9} | {:>12.3} | {:>12.1} | {}",
+            size,
+            size * size,
+            index_time * 1e3,
+            index_time * 1e9 / cells,
+            scan_columns
+        );
+    }
+}
@@ -0,0 +1,289 @@
+use std::{collections::HashMap, ops::Range};
+
+use crate::{
+    alphabet::Alphabet,
+    grid::{Grid, Neighborhood},
+    index,
+    schematic::SignMode,
+    topology::{SquareGrid, Topology},
+    Position,
+};
+
+// --- editable schematic ---
+// a schematic whose cells can be changed one at a time, keeping the totals of
+// both parts up to date by only re-evaluating what is around the edited cell,
+// symbols follow the default alphabet and numbers are unsigned, as in the puzzle,
+// cells touch the ones around them as given by the square grid
+pub struct EditableSchematic {
+    square: SquareGrid,
+    alphabet: Alphabet,
+    // numbers removed by an edit leave a hole, IDs are never reused
+    numbers: Vec<Option<EditedNumber>>,
+    cells: Grid<Option<usize>>,
+    // ratio of each gear, by the position of its '*'
+    gears: HashMap<Position, i64>,
+    part_numbers: i64,
+    gear_ratios: i64,
+}
+
+struct EditedNumber {
+    value: i64,
+    row: usize,
+    col_span: Range<usize>,
+    // what the number adds to the part numbers, its value when it touches a symbol
+    contribution: i64,
+}
+
+impl EditableSchematic {
+    pub fn new(input: &str) -> Self {
+        let grid = Grid::parse(input);
+
+        let mut schematic = EditableSchematic {
+            cells: Grid::filled(grid.width(), grid.height(), None),
+            square: SquareGrid {
+                grid,
+                neighborhood: Neighborhood::Eight,
+            },
+            alphabet: Alphabet::default(),
+            numbers: vec![],
+            gears: HashMap::new(),
+            part_numbers: 0,
+            gear_ratios: 0,
+        };
+
+        for row in 0..schematic.square.grid.height() {
+            let width = schematic.square.grid.width();
+            let number_ids = schematic.tokenize(row, 0..width);
+
+            for number_id in number_ids {
+                schematic.evaluate_number(number_id);
+            }
+        }
+
+        let stars = schematic
+        .square
+        .grid
+        .iter()
+        .filter(|(_, ch)| **ch == '*')
+        .map(|(pos, _)| pos)
+        .collect::<Vec<Position>>();
+
+        for pos in stars {
+            schematic.evaluate_gear(pos);
+        }
+
+        schematic
+    }
+
+    pub fn part_numbers(&self) -> i64 {
+        self.part_numbers
+    }
+
+    pub fn gear_ratios(&self) -> i64 {
+        self.gear_ratios
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, ch: char) -> Result<(), String> {
+        let pos = Position(row as i32, col as i32);
+
+        let old_ch = *self
+        .square
+        .grid
+        .get(pos)
+        .ok_or_else(|| format!("Cell ({}, {}) is outside of the schematic", row, col))?;
+
+        if ch == '\n' || ch == '\r' {
+            return Err("A cell cannot hold a line break".to_string());
+        }
+
+        if ch == old_ch {
+            return Ok(());
+        }
+
+        // numbers of the edited row that can merge, split or change,
+        // only the ones touching the cell or right next to it
+        let old_ids = self.numbers_in_row(row, col.saturating_sub(1)..col + 2);
+        let span = old_ids
+        .iter()
+        .map(|id| self.number(*id).col_span.clone())
+        .fold(col..col + 1, |span, number_span| span.start.min(number_span.start)..span.end.max(number_span.end));
+
+        // gears around the old numbers lose them
+        let mut gear_positions = self.stars_around(row, span.clone());
+
+        for id in &old_ids {
+            self.remove_number(*id);
+        }
+
+        *self.square.grid.get_mut(pos).unwrap() = ch;
+
+        // the digits around the cell are read again, a number can now
+        // reach a bit further than the old ones did
+        let new_ids = self.tokenize(row, span);
+
+        for id in &new_ids {
+            self.evaluate_number(*id);
+
+            let number_span = self.number(*id).col_span.clone();
+
+            for gear_pos in self.stars_around(row, number_span) {
+                if !gear_positions.contains(&gear_pos) {
+                    gear_positions.push(gear_pos);
+                }
+            }
+        }
+
+        // numbers above and below may have gained or lost a symbol
+        for neighbor in self.square.neighbors(pos).into_iter().filter(|neighbor| neighbor.0 != pos.0) {
+            if let Some(id) = self.cells.get(neighbor).copied().flatten() {
+                self.evaluate_number(id);
+            }
+        }
+
+        // and the cell may have become a gear or stopped being one
+        if !gear_positions.contains(&pos) {
+            gear_positions.push(pos);
+        }
+
+        for gear_pos in gear_positions {
+            self.evaluate_gear(gear_pos);
+        }
+
+        Ok(())
+    }
+
+    pub fn to_text(&self) -> String {
+        self.square.grid.rows().map(|row| row.iter().collect::<String>()).collect::<Vec<String>>().join("\n")
+    }
+
+    // --- helpers ---
+    fn number(&self, id: usize) -> &EditedNumber {
+        self.numbers[id].as_ref().unwrap()
+    }
+
+    // IDs of the numbers of a row covering at least one of the columns
+    fn numbers_in_row(&self, row: usize, cols: Range<usize>) -> Vec<usize> {
+        let mut ids: Vec<usize> = vec![];
+
+        for col in cols {
+            if let Some(id) = self.cells.get(Position(row as i32, col as i32)).copied().flatten() {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+        }
+
+        ids
+    }
+
+    // the '*' touching any of the columns of a row
+    fn stars_around(&self, row: usize, cols: Range<usize>) -> Vec<Position> {
+        let mut stars: Vec<Position> = vec![];
+
+        for col in cols {
+            for neighbor in self.square.neighbors(Position(row as i32, col as i32)) {
+                if self.square.get(neighbor) == Some('*') && !stars.contains(&neighbor) {
+                    stars.push(neighbor);
+                }
+            }
+        }
+
+        stars
+    }
+
+    // reads the numbers of a row found within the columns, extending them to their whole digits
+    fn tokenize(&mut self, row: usize, cols: Range<usize>) -> Vec<usize> {
+        let line = self.square.grid.row(row).unwrap();
+        let mut start = cols.start;
+        let mut end = cols.end.min(line.len());
+
+        while start > 0 && line[start - 1].is_ascii_digit() {
+            start -= 1;
+        }
+
+        while end < line.len() && line[end].is_ascii_digit() {
+            end += 1;
+        }
+
+        // numbers are read as `Schematic` reads them, columns being shifted back to the row
+        let found = index::numbers_in_row(&line[start..end], SignMode::Unsigned);
+
+        let mut ids = vec![];
+
+        for (value, col_span) in found {
+            let col_span = col_span.start + start..col_span.end + start;
+            let id = self.numbers.len();
+
+            for number_col in col_span.clone() {
+                *self.cells.get_mut(Position(row as i32, number_col as i32)).unwrap() = Some(id);
+            }
+
+            self.numbers.push(Some(EditedNumber {
+                value,
+                row,
+                col_span,
+                contribution: 0,
+            }));
+            ids.push(id);
+        }
+
+        ids
+    }
+
+    fn remove_number(&mut self, id: usize) {
+        let number = self.numbers[id].take().unwrap();
+        self.part_numbers -= number.contribution;
+
+        for col in number.col_span {
+            *self.cells.get_mut(Position(number.row as i32, col as i32)).unwrap() = None;
+        }
+    }
+
+    // whether the number touches a symbol, replacing what it added to the part numbers
+    fn evaluate_number(&mut self, id: usize) {
+        let number = self.number(id);
+        let row = number.row;
+
+        let touches_symbol = number.col_span.clone().any(|col| {
+            self.square
+            .neighbors(Position(row as i32, col as i32))
+            .into_iter()
+            .any(|neighbor| self.square.get(neighbor).is_some_and(|ch| self.alphabet.is_symbol(ch)))
+        });
+
+        let contribution = if touches_symbol { number.value } else { 0 };
+        let number = self.numbers[id].as_mut().unwrap();
+
+        self.part_numbers += contribution - number.contribution;
+        number.contribution = contribution;
+    }
+
+    // the ratio of the cell when it is a gear, replacing its previous one
+    fn evaluate_gear(&mut self, pos: Position) {
+        let ratio = if self.square.grid.get(pos) == Some(&'*') {
+            let mut ids: Vec<usize> = vec![];
+
+            for neighbor in self.square.neighbors(pos) {
+                if let Some(id) = self.cells.get(neighbor).copied().flatten() {
+                    if !ids.contains(&id) {
+                        ids.push(id);
+                    }
+                }
+            }
+
+            match ids.as_slice() {
+                [first, second] => self.number(*first).value * self.number(*second).value,
+                _ => 0,
+            }
+        } else {
+            0
+        };
+
+        let previous = match ratio {
+            0 => self.gears.remove(&pos),
+            _ => self.gears.insert(pos, ratio),
+        };
+
+        self.gear_ratios += ratio - previous.unwrap_or(0);
+    }
+}
@@ -0,0 +1,123 @@
+use crate::schematic::Schematic;
+
+// --- gear rules ---
+// how many parts a symbol must touch to be a gear
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arity {
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+type CombineFn = dyn Fn(&[i64]) -> i64;
+
+// how the values of the parts of a gear are turned into its ratio
+pub enum Combiner {
+    Product,
+    Sum,
+    Max,
+    Custom(Box<CombineFn>),
+}
+
+pub struct GearRule {
+    pub name: String,
+    // the symbols that can be a gear, any symbol when empty
+    pub symbols: Vec<char>,
+    pub arity: Arity,
+    pub combiner: Combiner,
+}
+
+impl GearRule {
+    pub fn new(name: &str, symbols: &str, arity: Arity, combiner: Combiner) -> Self {
+        GearRule {
+            name: name.to_string(),
+            symbols: symbols.chars().collect(),
+            arity,
+            combiner,
+        }
+    }
+
+    // the gear of part 2: a '*' touching exactly two parts, multiplied together
+    pub fn part_2() -> Self {
+        GearRule::new("gear", "*", Arity::Exactly(2), Combiner::Product)
+    }
+
+    fn matches(&self, kind: char, part_count: usize) -> bool {
+        let symbol_matches = self.symbols.is_empty() || self.symbols.contains(&kind);
+
+        let arity_matches = match self.arity {
+            Arity::Exactly(count) => part_count == count,
+            Arity::AtLeast(count) => part_count >= count,
+        };
+
+        symbol_matches && arity_matches
+    }
+
+    fn combine(&self, values: &[i64]) -> i64 {
+        match &self.combiner {
+            Combiner::Product => values.iter().product(),
+            Combiner::Sum => values.iter().sum(),
+            Combiner::Max => values.iter().max().copied().unwrap_or(0),
+            Combiner::Custom(combine) => combine(values),
+        }
+    }
+}
+
+// --- evaluation ---
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleResult {
+    pub name: String,
+    // each gear found by the rule, as (symbol ID, ratio)
+    pub gears: Vec<(usize, i64)>,
+    pub total: i64,
+}
+
+// every rule is checked against each symbol in a single pass over the schematic,
+// a symbol can be a gear for several rules at once
+pub fn evaluate_rules(schematic: &Schematic, rules: &[GearRule]) -> Vec<RuleResult> {
+    let symbols = schematic
+    .symbols
+    .iter()
+    .enumerate()
+    .map(|(symbol_id, symbol)| (symbol.kind, schematic.parts_adjacent_to(symbol_id)));
+
+    evaluate_symbols(symbols, |number_id| schematic.numbers[number_id].value, rules)
+}
+
+// the same pass on symbols given as (kind, IDs of the parts they touch), in symbol ID order,
+// whatever the kind of grid they come from
+pub fn evaluate_symbols<'a>(
+    symbols: impl Iterator<Item = (char, &'a [usize])>,
+    value_of: impl Fn(usize) -> i64,
+    rules: &[GearRule],
+) -> Vec<RuleResult> {
+    let mut results = rules
+    .iter()
+    .map(|rule| RuleResult {
+        name: rule.name.clone(),
+        gears: vec![],
+        total: 0,
+    })
+    .collect::<Vec<RuleResult>>();
+
+    for (symbol_id, (kind, parts)) in symbols.enumerate() {
+        // values are only gathered once per symbol, whatever the number of rules
+        let mut values: Option<Vec<i64>> = None;
+
+        for (rule, result) in rules.iter().zip(results.iter_mut()) {
+            if !rule.matches(kind, parts.len()) {
+                continue;
+            }
+
+            let values = values.get_or_insert_with(|| {
+                parts.iter().map(|number_id| value_of(*number_id)).collect()
+            });
+
+            let ratio = rule.combine(values);
+
+            result.gears.push((symbol_id, ratio));
+            result.total += ratio;
+        }
+    }
+
+    results
+}
@@ -0,0 +1,190 @@
+use serde::Serialize;
+
+use crate::schematic::Schematic;
+
+// --- adjacency graph ---
+// the bipartite graph of the schematic: numbers on one side, symbols on the other,
+// and an edge whenever a symbol touches a digit of a number
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeKind {
+    Number,
+    Symbol,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Node {
+    // "n<number ID>" or "s<symbol ID>", stable across formats
+    pub id: String,
+    pub kind: NodeKind,
+    // the value of a number, or the character of a symbol
+    pub label: String,
+    pub row: usize,
+    pub col: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Edge {
+    pub source: String,
+    pub target: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Graph {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+}
+
+impl Graph {
+    pub fn from_schematic(schematic: &Schematic) -> Self {
+        let numbers = schematic.numbers.iter().enumerate().map(|(number_id, number)| Node {
+            id: format!("n{}", number_id),
+            kind: NodeKind::Number,
+            label: number.value.to_string(),
+            row: number.row,
+            col: number.col_span.start,
+        });
+
+        let symbols = schematic.symbols.iter().enumerate().map(|(symbol_id, symbol)| Node {
+            id: format!("s{}", symbol_id),
+            kind: NodeKind::Symbol,
+            label: symbol.kind.to_string(),
+            row: symbol.pos.0 as usize,
+            col: symbol.pos.1 as usize,
+        });
+
+        // edges always go from the symbol to the number
+        let edges = (0..schematic.symbols.len())
+        .flat_map(|symbol_id| {
+            schematic.parts_adjacent_to(symbol_id).iter().map(move |number_id| Edge {
+                source: format!("s{}", symbol_id),
+                target: format!("n{}", number_id),
+            })
+        })
+        .collect::<Vec<Edge>>();
+
+        Graph {
+            nodes: numbers.chain(symbols).collect(),
+            edges,
+        }
+    }
+
+    // Graphviz, with numbers as boxes and symbols as circles
+    pub fn to_dot(&self) -> String {
+        let mut lines = vec!["graph schematic {".to_string()];
+
+        for node in &self.nodes {
+            let shape = match node.kind {
+                NodeKind::Number => "box",
+                NodeKind::Symbol => "circle",
+            };
+
+            lines.push(format!(
+                "    {} [label=\"{}\", shape={}, row={}, col={}];",
+                node.id,
+                node.label.replace('\\', "\\\\").replace('"', "\\\""),
+                shape,
+                node.row,
+                node.col
+            ));
+        }
+
+        for edge in &self.edges {
+            lines.push(format!("    {} -- {};", edge.source, edge.target));
+        }
+
+        lines.push("}".to_string());
+
+        lines.join("\n")
+    }
+
+    pub fn to_graphml(&self) -> String {
+        let mut lines = vec![
+            r#"<?xml version="1.0" encoding="UTF-8"?>"#.to_string(),
+            r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#.to_string(),
+            r#"  <key id="kind" for="node" attr.name="kind" attr.type="string"/>"#.to_string(),
+            r#"  <key id="label" for="node" attr.name="label" attr.type="string"/>"#.to_string(),
+            r#"  <key id="row" for="node" attr.name="row" attr.type="int"/>"#.to_string(),
+            r#"  <key id="col" for="node" attr.name="col" attr.type="int"/>"#.to_string(),
+            r#"  <graph id="schematic" edgedefault="undirected">"#.to_string(),
+        ];
+
+        for node in &self.nodes {
+            let kind = match node.kind {
+                NodeKind::Number => "number",
+                NodeKind::Symbol => "symbol",
+            };
+
+            lines.push(format!(r#"    <node id="{}">"#, node.id));
+            lines.push(format!(r#"      <data key="kind">{}</data>"#, kind));
+            lines.push(format!(r#"      <data key="label">{}</data>"#, escape_xml(&node.label)));
+            lines.push(format!(r#"      <data key="row">{}</data>"#, node.row));
+            lines.push(format!(r#"      <data key="col">{}</data>"#, node.col));
+            lines.push("    </node>".to_string());
+        }
+
+        for edge in &self.edges {
+            lines.push(format!(r#"    <edge source="{}" target="{}"/>"#, edge.source, edge.target));
+        }
+
+        lines.push("  </graph>".to_string());
+        lines.push("</graphml>".to_string());
+
+        lines.join("\n")
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Could not serialize graph")
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+}
+
+// --- connected components ---
+// part numbers linked through the symbols they share, as groups of number IDs,
+// each group sorted and groups ordered by their first number
+pub fn part_components(schematic: &Schematic) -> Vec<Vec<usize>> {
+    // union-find over the numbers, every symbol merging the parts it touches
+    let mut parents = (0..schematic.numbers.len()).collect::<Vec<usize>>();
+
+    fn root(parents: &mut [usize], mut id: usize) -> usize {
+        while parents[id] != id {
+            parents[id] = parents[parents[id]];
+            id = parents[id];
+        }
+
+        id
+    }
+
+    for symbol_id in 0..schematic.symbols.len() {
+        let parts = schematic.parts_adjacent_to(symbol_id);
+
+        for pair in parts.windows(2) {
+            let (first, second) = (root(&mut parents, pair[0]), root(&mut parents, pair[1]));
+            parents[first.max(second)] = first.min(second);
+        }
+    }
+
+    let mut components: Vec<Vec<usize>> = vec![];
+    let mut component_of_root: Vec<Option<usize>> = vec![None; schematic.numbers.len()];
+
+    for number_id in schematic.part_numbers() {
+        let number_root = root(&mut parents, number_id);
+
+        match component_of_root[number_root] {
+            Some(component_id) => components[component_id].push(number_id),
+            None => {
+                component_of_root[number_root] = Some(components.len());
+                components.push(vec![number_id]);
+            }
+        }
+    }
+
+    components
+}
@@ -0,0 +1,261 @@
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::Position;
+
+// --- neighborhoods ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighborhood {
+    // up, down, left and right
+    Four,
+    // the four above plus the diagonals
+    Eight,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edges {
+    // neighbors outside of the grid are dropped
+    Bounded,
+    // the grid wraps around, leaving one side brings you back on the other one
+    Toroidal,
+}
+
+impl Neighborhood {
+    pub fn offsets(&self) -> &'static [(i32, i32)] {
+        match self {
+            Neighborhood::Four => &[(-1, 0), (0, -1), (0, 1), (1, 0)],
+            Neighborhood::Eight => &[(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)],
+        }
+    }
+}
+
+// --- rows and columns ---
+// what to do with rows that are not as wide as the others
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RaggedRows {
+    // shorter rows are completed up to the widest one
+    Pad,
+    // rows must all be as wide as the first one
+    Reject,
+    // each row keeps its own width, cells past the end of a row do not exist,
+    // which is how lines were always read
+    #[default]
+    PerRow,
+}
+
+// what a column of the input is
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColumnUnit {
+    #[default]
+    Chars,
+    // user-perceived characters, an accent combined with its letter takes a single cell
+    Graphemes,
+}
+
+impl ColumnUnit {
+    pub fn split(&self, line: &str) -> Vec<char> {
+        match self {
+            ColumnUnit::Chars => line.chars().collect(),
+            ColumnUnit::Graphemes => line.graphemes(true).map(grapheme_cell).collect(),
+        }
+    }
+}
+
+// a cluster is stored as its composed char when it has one ("e" + accent is 'é'),
+// otherwise as its first char that is not a letter or a digit, so that a letter
+// carrying a mark is still read as a symbol rather than as the bare letter
+fn grapheme_cell(grapheme: &str) -> char {
+    let mut composed = grapheme.nfc();
+
+    match (composed.next(), composed.next()) {
+        (Some(ch), None) => ch,
+        _ => grapheme
+        .chars()
+        .find(|ch| !ch.is_alphanumeric())
+        .unwrap_or_else(|| grapheme.chars().next().unwrap()),
+    }
+}
+
+// a row whose width is not the expected one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RaggedRow {
+    pub row: usize,
+    pub width: usize,
+    pub expected: usize,
+}
+
+// --- grid ---
+// a grid stored row after row, positions are (row, column),
+// rows can be narrower than the grid when their width is kept per row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    row_widths: Vec<usize>,
+    cells: Vec<T>,
+}
+
+impl Grid<char> {
+    // rows keep their own width, so any input can be read
+    pub fn parse(input: &str) -> Self {
+        let rows = input.lines().map(|line| ColumnUnit::Chars.split(line)).collect();
+
+        Grid::from_ragged_rows(rows, RaggedRows::PerRow, '.').expect("Rows kept per row can not be ragged")
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    // ragged rows are completed with `fill`, whether they are padded or kept per row
+    pub fn from_ragged_rows(rows: Vec<Vec<T>>, ragged_rows: RaggedRows, fill: T) -> Result<Self, Vec<RaggedRow>> {
+        let row_widths = rows.iter().map(|row| row.len()).collect::<Vec<usize>>();
+        let width = row_widths.iter().copied().max().unwrap_or(0);
+
+        if ragged_rows == RaggedRows::Reject {
+            let expected = row_widths.first().copied().unwrap_or(0);
+
+            let ragged = row_widths
+            .iter()
+            .enumerate()
+            .filter(|(_, row_width)| **row_width != expected)
+            .map(|(row, row_width)| RaggedRow { row, width: *row_width, expected })
+            .collect::<Vec<RaggedRow>>();
+
+            if !ragged.is_empty() {
+                return Err(ragged);
+            }
+        }
+
+        let row_widths = match ragged_rows {
+            RaggedRows::PerRow => row_widths,
+            _ => vec![width; rows.len()],
+        };
+
+        let cells = rows
+        .into_iter()
+        .flat_map(|mut row| {
+            row.resize(width, fill.clone());
+            row
+        })
+        .collect::<Vec<T>>();
+
+        Ok(Grid {
+            width,
+            height: row_widths.len(),
+            row_widths,
+            cells,
+        })
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn filled(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            row_widths: vec![width; height],
+            cells: vec![value; width * height],
+        }
+    }
+
+    // the width of the widest row
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn row_width(&self, row: usize) -> Option<usize> {
+        self.row_widths.get(row).copied()
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        position.0 >= 0
+            && position.1 >= 0
+            && (position.0 as usize) < self.height
+            && (position.1 as usize) < self.row_widths[position.0 as usize]
+    }
+
+    fn index(&self, position: Position) -> Option<usize> {
+        if self.contains(position) {
+            Some(position.0 as usize * self.width + position.1 as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, position: Position) -> Option<&T> {
+        self.index(position).map(|index| &self.cells[index])
+    }
+
+    pub fn get_mut(&mut self, position: Position) -> Option<&mut T> {
+        self.index(position).map(|index| &mut self.cells[index])
+    }
+
+    pub fn row(&self, row: usize) -> Option<&[T]> {
+        if row < self.height {
+            Some(&self.cells[row * self.width..row * self.width + self.row_widths[row]])
+        } else {
+            None
+        }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(|row| self.row(row).unwrap())
+    }
+
+    pub fn column(&self, column: usize) -> impl Iterator<Item = &T> {
+        // an out of bounds column is simply empty, rows too narrow for it are skipped
+        (0..self.height)
+        .filter(move |row| column < self.row_widths[*row])
+        .map(move |row| &self.cells[row * self.width + column])
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|column| self.column(column))
+    }
+
+    // every cell with its position, row after row
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.cells
+        .iter()
+        .enumerate()
+        .map(|(index, cell)| (Position((index / self.width) as i32, (index % self.width) as i32), cell))
+        .filter(|(position, _)| self.contains(*position))
+    }
+
+    pub fn neighbors(&self, position: Position, neighborhood: Neighborhood, edges: Edges) -> impl Iterator<Item = Position> {
+        let mut neighbors: Vec<Position> = vec![];
+
+        for (row_offset, col_offset) in neighborhood.offsets() {
+            let mut neighbor = Position(position.0 + row_offset, position.1 + col_offset);
+
+            if edges == Edges::Toroidal && self.width > 0 && self.height > 0 {
+                neighbor = Position(
+                    neighbor.0.rem_euclid(self.height as i32),
+                    neighbor.1.rem_euclid(self.width as i32),
+                );
+            }
+
+            // on grids thinner than 3 cells, wrapping around can reach the same cell twice,
+            // or even the cell itself
+            if self.contains(neighbor) && neighbor != position && !neighbors.contains(&neighbor) {
+                neighbors.push(neighbor);
+            }
+        }
+
+        neighbors.into_iter()
+    }
+
+    pub fn neighbors4(&self, position: Position) -> impl Iterator<Item = Position> {
+        self.neighbors(position, Neighborhood::Four, Edges::Bounded)
+    }
+
+    pub fn neighbors8(&self, position: Position) -> impl Iterator<Item = Position> {
+        self.neighbors(position, Neighborhood::Eight, Edges::Bounded)
+    }
+}
@@ -0,0 +1,383 @@
+use crate::{
+    gears::{self, GearRule},
+    schematic::Schematic,
+};
+
+// --- colors ---
+type Color = [u8; 3];
+
+const BACKGROUND: Color = [255, 255, 255];
+const GRID_LINE: Color = [225, 225, 225];
+const PART_FILL: Color = [205, 240, 205];
+const PART_STROKE: Color = [40, 140, 40];
+const UNATTACHED_FILL: Color = [250, 212, 212];
+const UNATTACHED_STROKE: Color = [180, 40, 40];
+const SYMBOL_FILL: Color = [250, 220, 120];
+const GEAR_FILL: Color = [220, 150, 230];
+const LINK: Color = [70, 110, 200];
+const TEXT: Color = [20, 20, 20];
+
+fn hex(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+// --- layout ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageOptions {
+    // side of a cell, in pixels
+    pub cell_size: u32,
+    // lines between each symbol and the numbers it touches
+    pub links: bool,
+}
+
+impl Default for ImageOptions {
+    fn default() -> Self {
+        ImageOptions {
+            cell_size: 16,
+            links: true,
+        }
+    }
+}
+
+impl ImageOptions {
+    // cells need at least one pixel, grid lines are drawn every `cell_size` pixels
+    pub fn validate(&self) -> Result<(), String> {
+        if self.cell_size == 0 {
+            return Err("Cell size must be at least 1 pixel".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+// what is drawn, shared by both output formats, in pixels
+struct Layout {
+    width: u32,
+    height: u32,
+    cell_size: u32,
+    // (x, y, width, value, attached)
+    number_boxes: Vec<(u32, u32, u32, i64, bool)>,
+    // (x, y, kind, gear)
+    symbol_cells: Vec<(u32, u32, char, bool)>,
+    // (from x, from y, to x, to y)
+    links: Vec<(u32, u32, u32, u32)>,
+}
+
+fn layout(schematic: &Schematic, options: ImageOptions) -> Layout {
+    let cell_size = options.cell_size;
+    let gears = &gears::evaluate_rules(schematic, &[GearRule::part_2()])[0];
+
+    let number_boxes = schematic.numbers
+    .iter()
+    .enumerate()
+    .map(|(number_id, number)| {
+        (
+            number.col_span.start as u32 * cell_size,
+            number.row as u32 * cell_size,
+            number.col_span.len() as u32 * cell_size,
+            number.value,
+            !schematic.symbols_adjacent_to(number_id).is_empty(),
+        )
+    })
+    .collect::<Vec<(u32, u32, u32, i64, bool)>>();
+
+    let symbol_cells = schematic.symbols
+    .iter()
+    .enumerate()
+    .map(|(symbol_id, symbol)| {
+        (
+            symbol.pos.1 as u32 * cell_size,
+            symbol.pos.0 as u32 * cell_size,
+            symbol.kind,
+            gears.gears.iter().any(|(gear_id, _)| *gear_id == symbol_id),
+        )
+    })
+    .collect::<Vec<(u32, u32, char, bool)>>();
+
+    // links go from the center of the symbol to the center of the number box
+    let mut links = vec![];
+
+    if options.links {
+        for (symbol_id, (x, y, _, _)) in symbol_cells.iter().enumerate() {
+            for number_id in schematic.parts_adjacent_to(symbol_id) {
+                let (box_x, box_y, box_width, _, _) = number_boxes[*number_id];
+
+                links.push((x + cell_size / 2, y + cell_size / 2, box_x + box_width / 2, box_y + cell_size / 2));
+            }
+        }
+    }
+
+    Layout {
+        width: schematic.grid.width() as u32 * cell_size,
+        height: schematic.grid.height() as u32 * cell_size,
+        cell_size,
+        number_boxes,
+        symbol_cells,
+        links,
+    }
+}
+
+// --- SVG ---
+pub fn to_svg(schematic: &Schematic, options: ImageOptions) -> Result<String, String> {
+    options.validate()?;
+
+    let layout = layout(schematic, options);
+    let cell_size = layout.cell_size;
+    let font_size = cell_size * 3 / 4;
+
+    let mut svg = vec![
+        format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}" font-family="monospace" font-size="{}">"#,
+            layout.width, layout.height, layout.width, layout.height, font_size
+        ),
+        format!(r#"<rect width="{}" height="{}" fill="{}"/>"#, layout.width, layout.height, hex(BACKGROUND)),
+    ];
+
+    // cells, as a single pattern of squares
+    svg.push(format!(
+        r#"<defs><pattern id="cell" width="{}" height="{}" patternUnits="userSpaceOnUse"><rect width="{}" height="{}" fill="none" stroke="{}"/></pattern></defs>"#,
+        cell_size,
+        cell_size,
+        cell_size,
+        cell_size,
+        hex(GRID_LINE)
+    ));
+    svg.push(format!(r#"<rect class="cells" width="{}" height="{}" fill="url(#cell)"/>"#, layout.width, layout.height));
+
+    for (from_x, from_y, to_x, to_y) in &layout.links {
+        svg.push(format!(
+            r#"<line class="link" x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="{}"/>"#,
+            from_x,
+            from_y,
+            to_x,
+            to_y,
+            hex(LINK),
+            (cell_size / 8).max(1)
+        ));
+    }
+
+    for (x, y, width, value, attached) in &layout.number_boxes {
+        let (class, fill, stroke) = if *attached {
+            ("part", PART_FILL, PART_STROKE)
+        } else {
+            ("unattached", UNATTACHED_FILL, UNATTACHED_STROKE)
+        };
+
+        svg.push(format!(
+            r#"<rect class="number {}" x="{}" y="{}" width="{}" height="{}" fill="{}" stroke="{}"/>"#,
+            class,
+            x,
+            y,
+            width,
+            cell_size,
+            hex(fill),
+            hex(stroke)
+        ));
+        svg.push(text(x + width / 2, *y, cell_size, &value.to_string()));
+    }
+
+    for (x, y, kind, gear) in &layout.symbol_cells {
+        let (class, fill) = if *gear { ("gear", GEAR_FILL) } else { ("symbol", SYMBOL_FILL) };
+
+        svg.push(format!(
+            r#"<rect class="{}" x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+            class,
+            x,
+            y,
+            cell_size,
+            cell_size,
+            hex(fill)
+        ));
+        svg.push(text(x + cell_size / 2, *y, cell_size, &kind.to_string()));
+    }
+
+    svg.push("</svg>".to_string());
+
+    Ok(svg.join("\n"))
+}
+
+// a label centered horizontally on x, in the cell row starting at y
+fn text(x: u32, y: u32, cell_size: u32, content: &str) -> String {
+    let escaped = content
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;");
+
+    format!(
+        r#"<text x="{}" y="{}" text-anchor="middle" dominant-baseline="central" fill="{}">{}</text>"#,
+        x,
+        y + cell_size / 2,
+        hex(TEXT),
+        escaped
+    )
+}
+
+// --- PNG ---
+// the same drawing, rasterized by hand, labels only use a tiny built-in digit font
+pub fn to_png(schematic: &Schematic, options: ImageOptions) -> Result<Vec<u8>, String> {
+    options.validate()?;
+
+    let layout = layout(schematic, options);
+
+    // PNG images can not be empty, an empty schematic is drawn as a single blank cell
+    if layout.width == 0 || layout.height == 0 {
+        return Ok(Canvas::new(layout.cell_size, layout.cell_size).encode());
+    }
+
+    let mut canvas = Canvas::new(layout.width, layout.height);
+    let cell_size = layout.cell_size;
+
+    // cell borders
+    for x in (0..layout.width).step_by(cell_size as usize) {
+        canvas.fill_rect(x, 0, 1, layout.height, GRID_LINE);
+    }
+
+    for y in (0..layout.height).step_by(cell_size as usize) {
+        canvas.fill_rect(0, y, layout.width, 1, GRID_LINE);
+    }
+
+    for (from_x, from_y, to_x, to_y) in &layout.links {
+        canvas.line(*from_x as i64, *from_y as i64, *to_x as i64, *to_y as i64, LINK);
+    }
+
+    for (x, y, width, value, attached) in &layout.number_boxes {
+        let (fill, stroke) = if *attached {
+            (PART_FILL, PART_STROKE)
+        } else {
+            (UNATTACHED_FILL, UNATTACHED_STROKE)
+        };
+
+        canvas.fill_rect(*x, *y, *width, cell_size, stroke);
+        canvas.fill_rect(x + 1, y + 1, width.saturating_sub(2), cell_size.saturating_sub(2), fill);
+        canvas.digits(x + width / 2, y + cell_size / 2, cell_size, &value.to_string());
+    }
+
+    for (x, y, _, gear) in &layout.symbol_cells {
+        let fill = if *gear { GEAR_FILL } else { SYMBOL_FILL };
+
+        canvas.fill_rect(*x, *y, cell_size, cell_size, fill);
+    }
+
+    Ok(canvas.encode())
+}
+
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32) -> Self {
+        Canvas {
+            width,
+            height,
+            pixels: BACKGROUND.repeat((width * height) as usize),
+        }
+    }
+
+    fn set(&mut self, x: i64, y: i64, color: Color) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+
+        let index = ((y as u32 * self.width + x as u32) * 3) as usize;
+        self.pixels[index..index + 3].copy_from_slice(&color);
+    }
+
+    fn fill_rect(&mut self, x: u32, y: u32, width: u32, height: u32, color: Color) {
+        for py in y..y + height {
+            for px in x..x + width {
+                self.set(px as i64, py as i64, color);
+            }
+        }
+    }
+
+    // Bresenham's line algorithm
+    fn line(&mut self, mut x0: i64, mut y0: i64, x1: i64, y1: i64, color: Color) {
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let step_x = if x0 < x1 { 1 } else { -1 };
+        let step_y = if y0 < y1 { 1 } else { -1 };
+        let mut error = dx + dy;
+
+        loop {
+            self.set(x0, y0, color);
+
+            if x0 == x1 && y0 == y1 {
+                break;
+            }
+
+            let doubled_error = 2 * error;
+
+            if doubled_error >= dy {
+                error += dy;
+                x0 += step_x;
+            }
+
+            if doubled_error <= dx {
+                error += dx;
+                y0 += step_y;
+            }
+        }
+    }
+
+    // digits centered on (x, y), each one 3x5 font pixels scaled to the cell,
+    // with the sign of negative numbers
+    fn digits(&mut self, x: u32, y: u32, cell_size: u32, digits: &str) {
+        const FONT: [[u8; 5]; 10] = [
+            [0b111, 0b101, 0b101, 0b101, 0b111],
+            [0b010, 0b110, 0b010, 0b010, 0b111],
+            [0b111, 0b001, 0b111, 0b100, 0b111],
+            [0b111, 0b001, 0b111, 0b001, 0b111],
+            [0b101, 0b101, 0b111, 0b001, 0b001],
+            [0b111, 0b100, 0b111, 0b001, 0b111],
+            [0b111, 0b100, 0b111, 0b101, 0b111],
+            [0b111, 0b001, 0b001, 0b001, 0b001],
+            [0b111, 0b101, 0b111, 0b101, 0b111],
+            [0b111, 0b101, 0b111, 0b001, 0b111],
+        ];
+        const MINUS: [u8; 5] = [0b000, 0b000, 0b111, 0b000, 0b000];
+
+        let glyphs = digits
+        .chars()
+        .filter_map(|ch| match ch {
+            '-' => Some(MINUS),
+            _ => ch.to_digit(10).map(|digit| FONT[digit as usize]),
+        })
+        .collect::<Vec<[u8; 5]>>();
+
+        let scale = (cell_size / 8).max(1);
+        // a glyph is 3 font pixels wide, plus 1 of spacing
+        let total_width = (glyphs.len() as u32 * 4 * scale).saturating_sub(scale);
+        let left = x.saturating_sub(total_width / 2);
+        let top = y.saturating_sub(5 * scale / 2);
+
+        for (index, glyph) in glyphs.iter().enumerate() {
+            let glyph_left = left + index as u32 * 4 * scale;
+
+            for (font_y, bits) in glyph.iter().enumerate() {
+                for font_x in 0..3 {
+                    if bits & (0b100 >> font_x) != 0 {
+                        self.fill_rect(glyph_left + font_x * scale, top + font_y as u32 * scale, scale, scale, TEXT);
+                    }
+                }
+            }
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+
+        {
+            let mut encoder = png::Encoder::new(&mut bytes, self.width, self.height);
+            encoder.set_color(png::ColorType::Rgb);
+            encoder.set_depth(png::BitDepth::Eight);
+
+            let mut writer = encoder.write_header().expect("Could not write PNG header");
+            writer.write_image_data(&self.pixels).expect("Could not write PNG data");
+        }
+
+        bytes
+    }
+}
@@ -0,0 +1,98 @@
+use std::{collections::HashMap, hash::Hash, ops::Range};
+
+use crate::{schematic::SignMode, topology::Topology};
+
+// --- number index ---
+// every cell covered by a number points to it, so the numbers around
+// a symbol are found by looking at its neighbors only
+pub struct NumberIndex<C> {
+    // value and cells of each number, sign included
+    pub numbers: Vec<(i64, Vec<C>)>,
+    cells: HashMap<C, usize>,
+}
+
+impl<C: Copy + Eq + Hash> NumberIndex<C> {
+    // numbers are read along the rows of the topology, so their columns
+    // are counted the same way as its cells
+    pub fn build<T: Topology<Cell = C>>(topology: &T, sign_mode: SignMode) -> Self {
+        let mut numbers: Vec<(i64, Vec<C>)> = vec![];
+        let mut cells: HashMap<C, usize> = HashMap::new();
+
+        // iterating over rows
+        for row in topology.rows() {
+            let line = row.iter().map(|cell| topology.get(*cell).unwrap()).collect::<Vec<char>>();
+
+            // foreach number in the row, we store it and mark the cells it covers
+            for (value, col_span) in numbers_in_row(&line, sign_mode) {
+                for col in col_span.clone() {
+                    cells.insert(row[col], numbers.len());
+                }
+
+                numbers.push((value, row[col_span].to_vec()));
+            }
+        }
+
+        NumberIndex { numbers, cells }
+    }
+
+    pub fn number_at(&self, cell: C) -> Option<usize> {
+        self.cells.get(&cell).copied()
+    }
+
+    // distinct numbers touching the cell, a number is only given once
+    // even when several of its digits touch the cell
+    pub fn numbers_around<T: Topology<Cell = C>>(&self, topology: &T, cell: C) -> Vec<usize> {
+        let mut ids: Vec<usize> = vec![];
+
+        for neighbor in topology.neighbors(cell) {
+            if let Some(id) = self.number_at(neighbor) {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+        }
+
+        ids
+    }
+}
+
+// the numbers of a row with the columns they cover, sign included, end excluded
+pub fn numbers_in_row(line: &[char], sign_mode: SignMode) -> Vec<(i64, Range<usize>)> {
+    let mut numbers = vec![];
+    let mut col = 0;
+
+    // foreach run of digits in the row
+    while col < line.len() {
+        if !line[col].is_ascii_digit() {
+            col += 1;
+            continue;
+        }
+
+        let digits_start = col;
+
+        while col < line.len() && line[col].is_ascii_digit() {
+            col += 1;
+        }
+
+        let start = match sign_mode {
+            SignMode::Unsigned => digits_start,
+            SignMode::Signed => signed_start(line, digits_start),
+        };
+
+        numbers.push((line[start..col].iter().collect::<String>().parse::<i64>().unwrap(), start..col));
+    }
+
+    numbers
+}
+
+// where a number starting with the digit at `start` really starts, once its sign is included,
+// a sign only counts when it is not itself right after a digit
+fn signed_start(line: &[char], start: usize) -> usize {
+    match start {
+        0 => 0,
+        _ if !matches!(line[start - 1], '-' | '+') => start,
+        1 => 0,
+        _ if line[start - 2].is_ascii_digit() => start,
+        _ => start - 1,
+    }
+}
@@ -1,194 +1,272 @@
-use std::collections::HashMap;
+mod alphabet;
+mod bench;
+mod editor;
+mod gears;
+mod graph;
+mod grid;
+mod image;
+mod index;
+mod render;
+mod schematic;
+mod streaming;
+mod topology;
 
-use regex::Regex;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use alphabet::Alphabet;
+use gears::{Arity, Combiner, GearRule};
+use grid::{ColumnUnit, Edges, Grid, Neighborhood, RaggedRows};
+use index::NumberIndex;
+use schematic::{ParseOptions, Schematic, SignMode};
+use topology::{HexGrid, SquareGrid, Topology, TopologySchematic, VoxelGrid};
 
 const PUZZLE_INPUT: &str = "input.txt";
 const EXAMPLE_INPUT_PART_1: &str = "example1.txt";
 const EXAMPLE_INPUT_PART_2: &str = "example2.txt";
+const EXAMPLE_INPUT_SHARED_NUMBER: &str = "example_shared_number.txt";
+const EXAMPLE_INPUT_HUBS: &str = "example_hubs.txt";
+const EXAMPLE_INPUT_SIGNED: &str = "example_signed.txt";
+const EXAMPLE_INPUT_ALPHABET: &str = "example_alphabet.txt";
+const EXAMPLE_INPUT_RAGGED: &str = "example_ragged.txt";
+const EXAMPLE_INPUT_HEX: &str = "example_hex.txt";
+const EXAMPLE_INPUT_VOXEL: &str = "example_voxel.txt";
 
-fn part_1(file_path: &str) -> u32 {
+fn part_1(file_path: &str, counting_mode: CountingMode) -> i64 {
     // loading input
     let input = std::fs::read_to_string(file_path).unwrap();
 
-    // storing limits for rows and columns
-    let num_rows = input.lines().count();
-    let num_cols = input.lines().next().unwrap().chars().count();
-
-    // storing numbers and symbols
-    let mut numbers: Vec<(&str, Position)> = vec![];
-    let mut symbols: Vec<(char, Position)> = vec![];
-
-    // storing part numbers for sum
-    let mut part_numbers: Vec<u32> = vec![];
-
-    // setting up regex for numbers
-    let re = Regex::new(r"\d+").unwrap();
-
-    // iterating over lines
-    for (idx, line) in input.lines().enumerate() {
-        // foreach number in line
-        // we store it and its position
-        for m in re.find_iter(line) {
-            numbers.push((
-                m.as_str(),
-                Position(idx as i32, m.start() as i32),
-            ));
-        }
+    sum_part_numbers(&input, counting_mode)
+}
 
-        // and we store symbols and their position
-        for (col, ch) in line.chars().enumerate() {
-            if !ch.is_ascii_digit() && ch != '.' {
-                symbols.push((ch, Position(idx as i32, col as i32)));
-            }
+fn part_2(file_path: &str) -> i64 {
+    // loading input
+    let input = std::fs::read_to_string(file_path).unwrap();
+
+    sum_gear_ratios(&input)
+}
+
+fn sum_part_numbers(input: &str, counting_mode: CountingMode) -> i64 {
+    let schematic = Schematic::parse(input);
+
+    schematic.numbers
+    .iter()
+    .enumerate()
+    .map(|(number_id, number)| {
+        let adjacent_symbols = schematic.symbols_adjacent_to(number_id).len() as i64;
+
+        match counting_mode {
+            // a number touching several symbols is still a single part number
+            CountingMode::PerNumber => number.value * adjacent_symbols.min(1),
+            CountingMode::PerAdjacentSymbol => number.value * adjacent_symbols,
         }
-    }
+    })
+    .sum()
+}
+
+fn sum_gear_ratios(input: &str) -> i64 {
+    let schematic = Schematic::parse(input);
+
+    // a gear is a '*' connecting exactly two numbers, its ratio is their product
+    gears::evaluate_rules(&schematic, &[GearRule::part_2()])[0].total
+}
+
+// --- helpers ---
+// both parts on any topology, with the same rules as on the puzzle grid
+fn topology_sums<T: Topology>(topology: &T, options: &ParseOptions) -> (i64, i64) {
+    let schematic = TopologySchematic::build(topology, options);
+
+    (schematic.sum_part_numbers(), schematic.evaluate_rules(&[GearRule::part_2()])[0].total)
+}
+
+// how part numbers are added up in part 1, each occurrence of a number
+// is counted separately in both modes, even when two of them share the same value
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum CountingMode {
+    // once, however many symbols it touches
+    PerNumber,
+    // once for each symbol it touches, as the first version of part 1 did
+    PerAdjacentSymbol,
+}
+
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Position(pub i32, pub i32);
+
+
 
-    // for each number we check if it is in the range of a symbol
-    for (number, start_position) in numbers.iter() {
-        // determining end position of number
-        let num_end_pos = Position(start_position.0, start_position.1 + number.len() as i32 - 1);
-
-        // checking if number is in range of a symbol
-        for (_, symbol_pos) in symbols.iter() {
-            // creating positions of all neighbors
-            let neighbors = [
-                Position(symbol_pos.0 - 1, symbol_pos.1 - 1),
-                Position(symbol_pos.0 - 1, symbol_pos.1),
-                Position(symbol_pos.0 - 1, symbol_pos.1 + 1),
-                Position(symbol_pos.0, symbol_pos.1 - 1),
-                Position(symbol_pos.0, symbol_pos.1 + 1),
-                Position(symbol_pos.0 + 1, symbol_pos.1 - 1),
-                Position(symbol_pos.0 + 1, symbol_pos.1),
-                Position(symbol_pos.0 + 1, symbol_pos.1 + 1),
-            ]
-            .into_iter()
-            .filter(|p| {
-                p.0 >= 0 && p.1 >= 0 && p.0 < num_rows as i32 && p.1 < num_cols as i32
-            })
-            .collect::<Vec<Position>>();
-
-            // checking if number is in range of symbol
-            for neighbor in neighbors.iter() {
-                if neighbor.0 == start_position.0
-                    && neighbor.1 >= start_position.1
-                    && neighbor.1 <= num_end_pos.1
-                {
-                    part_numbers.push(number.parse::<u32>().unwrap());
-                    break;
+// --- commands ---
+// usage: day_3 bench
+//        day_3 graph <file> <dot|graphml|json>
+//        day_3 components <file>
+//        day_3 render <file> [--legend] [--totals]
+//        day_3 stream <file|->
+//        day_3 topology <file> <square8|square4|hex|voxel> [validate flags]
+//        day_3 validate <file> [--blanks <chars>] [--symbols <chars>] [--ignore <chars>] [--reject <chars>]
+//                           [--ragged <pad|reject|per-row>] [--columns <chars|graphemes>]
+//        day_3 edit <file>, then "<row> <col> <char>" lines on the standard input
+//        day_3 export <file> <output.svg|output.png> [--no-links]
+fn run_command(args: &[String]) {
+    match args.iter().map(String::as_str).collect::<Vec<&str>>().as_slice() {
+        ["bench"] => bench::run(),
+        ["edit", file_path] => {
+            let input = std::fs::read_to_string(file_path).unwrap();
+            let mut schematic = editor::EditableSchematic::new(&input);
+
+            println!("part numbers: {}, gear ratios: {}", schematic.part_numbers(), schematic.gear_ratios());
+
+            for line in std::io::stdin().lines() {
+                let line = line.unwrap();
+                let edit = match line.split_whitespace().collect::<Vec<&str>>().as_slice() {
+                    [row, col, ch] if ch.chars().count() == 1 => match (row.parse::<usize>(), col.parse::<usize>()) {
+                        (Ok(row), Ok(col)) => schematic.set(row, col, ch.chars().next().unwrap()),
+                        _ => Err(format!("Invalid position: {}", line)),
+                    },
+                    _ => Err(format!("Expected \"<row> <col> <char>\": {}", line)),
+                };
+
+                match edit {
+                    Ok(()) => println!("part numbers: {}, gear ratios: {}", schematic.part_numbers(), schematic.gear_ratios()),
+                    Err(error) => eprintln!("error: {}", error),
                 }
             }
         }
-    }
-    part_numbers.iter().sum()
-}
+        ["export", file_path, output_path, flags @ ..] => {
+            let input = std::fs::read_to_string(file_path).unwrap();
+            let schematic = Schematic::parse(&input);
+            let options = image::ImageOptions {
+                links: !flags.contains(&"--no-links"),
+                ..image::ImageOptions::default()
+            };
 
-fn part_2(file_path: &str) -> u32 {
-    // loading input
-    let input = std::fs::read_to_string(file_path).unwrap();
+            let bytes = if output_path.ends_with(".png") {
+                image::to_png(&schematic, options).unwrap()
+            } else {
+                image::to_svg(&schematic, options).unwrap().into_bytes()
+            };
 
-    // storing limits for rows and columns
-    let num_rows = input.lines().count();
-    let num_cols = input.lines().next().unwrap().chars().count();
-
-    // storing numbers and gears
-    let mut numbers: Vec<(&str, Position)> = vec![];
-    let mut gears: Vec<(char, Position)> = vec![];
-
-    // storing gear and neighbor numbers
-    let mut gear_table: HashMap<Position, Vec<u32>> = HashMap::new();
-
-    // setting up regex for numbers
-    let re = Regex::new(r"\d+").unwrap();
-
-    // iterating over lines
-    for (row, line) in input.lines().enumerate() {
-        // foreach number in line
-        for m in re.find_iter(line) {
-            // we store it and its position
-            numbers.push((
-                m.as_str(),
-                Position(row as i32, m.start() as i32),
-            ));
+            std::fs::write(output_path, bytes).expect("Could not write image");
         }
+        ["graph", file_path, format] => {
+            let input = std::fs::read_to_string(file_path).unwrap();
+            let graph = graph::Graph::from_schematic(&Schematic::parse(&input));
 
-        // and we store gears and their position
-        for (col, ch) in line.chars().enumerate() {
-            if ch == '*' {
-                gears.push((ch, Position(row as i32, col as i32)));
+            match *format {
+                "dot" => println!("{}", graph.to_dot()),
+                "graphml" => println!("{}", graph.to_graphml()),
+                "json" => println!("{}", graph.to_json()),
+                _ => panic!("Unknown graph format: {}", format),
             }
         }
-    }
+        ["components", file_path] => {
+            let input = std::fs::read_to_string(file_path).unwrap();
+            let schematic = Schematic::parse(&input);
 
-    // for each number we check if it is in the range of a gear
-    for (num, start_position) in numbers.iter() {
-        // determining end position of number
-        let num_end_pos = Position(start_position.0, start_position.1 + num.len() as i32 - 1);
-
-        // looping over gears
-        for (_, gear_pos) in gears.iter() {
-
-            // creating possible positions of all neighbors
-            let neighbors = [
-                Position(gear_pos.0 - 1, gear_pos.1 - 1),
-                Position(gear_pos.0 - 1, gear_pos.1),
-                Position(gear_pos.0 - 1, gear_pos.1 + 1),
-                Position(gear_pos.0, gear_pos.1 - 1),
-                Position(gear_pos.0, gear_pos.1 + 1),
-                Position(gear_pos.0 + 1, gear_pos.1 - 1),
-                Position(gear_pos.0 + 1, gear_pos.1),
-                Position(gear_pos.0 + 1, gear_pos.1 + 1),
-            ]
-            .into_iter()
-            // filter out positions that are out of bounds
-            .filter(|p| p.0 >= 0 && p.1 >= 0 && p.0 < num_rows as i32 && p.1 < num_cols as i32)
-            .collect::<Vec<Position>>();
-
-            // checking if number is in range of symbol
-            for neighbor in neighbors.iter() {
-                if neighbor.0 == start_position.0
-                    && neighbor.1 >= start_position.1
-                    && neighbor.1 <= num_end_pos.1
-                {
-                    let num: u32 = num.parse::<u32>().unwrap();
-
-                    // storing gear position and neighbor number
-                    gear_table
-                        .entry(*gear_pos)
-                        .and_modify(|v| v.push(num))
-                        .or_insert(vec![num]);
-
-                    break;
-                }
+            for component in graph::part_components(&schematic) {
+                let values = component
+                .iter()
+                .map(|number_id| schematic.numbers[*number_id].value.to_string())
+                .collect::<Vec<String>>();
+
+                println!("{}", values.join(" "));
             }
         }
-    }
+        ["render", file_path, flags @ ..] => {
+            let input = std::fs::read_to_string(file_path).unwrap();
+            let options = render::RenderOptions {
+                legend: flags.contains(&"--legend"),
+                row_totals: flags.contains(&"--totals"),
+            };
+
+            println!("{}", render::render(&Schematic::parse(&input), options));
+        }
+        ["stream", file_path] => {
+            // "-" reads the schematic from the standard input
+            let totals = if *file_path == "-" {
+                streaming::solve(std::io::stdin().lock())
+            } else {
+                streaming::solve(std::io::BufReader::new(std::fs::File::open(file_path).unwrap()))
+            }
+            .expect("Could not read schematic");
+
+            println!("rows: {}", totals.rows);
+            println!("part numbers: {}", totals.part_numbers);
+            println!("gear ratios: {}", totals.gear_ratios);
+        }
+        ["topology", file_path, topology, flags @ ..] => {
+            let input = std::fs::read_to_string(file_path).unwrap();
+            let options = parse_options(flags);
+            fn invalid<T>(report: alphabet::ValidationReport) -> T {
+                panic!("Invalid schematic:\n{}", report)
+            }
+
+            let (part_numbers, gear_ratios) = match *topology {
+                "square8" => topology_sums(&SquareGrid::parse(&input, Neighborhood::Eight, &options).unwrap_or_else(invalid), &options),
+                "square4" => topology_sums(&SquareGrid::parse(&input, Neighborhood::Four, &options).unwrap_or_else(invalid), &options),
+                "hex" => topology_sums(&HexGrid::parse(&input, &options).unwrap_or_else(invalid), &options),
+                "voxel" => topology_sums(&VoxelGrid::parse(&input, &options).unwrap_or_else(invalid), &options),
+                _ => panic!("Unknown topology: {}", topology),
+            };
 
-    // calculating gear ratios
-    let mut gear_ratios: Vec<u32> = vec![];
+            println!("part numbers: {}", part_numbers);
+            println!("gear ratios: {}", gear_ratios);
+        }
+        ["validate", file_path, flags @ ..] => {
+            let input = std::fs::read_to_string(file_path).unwrap();
+            let report = match schematic::load_grid(&input, &parse_options(flags)) {
+                Ok((_, report)) => report,
+                Err(report) => panic!("{} is rejected:\n{}", file_path, report),
+            };
 
-    for v in gear_table.values() {
-        // if there is two numbers, they are connected by a gear
-        // and we can calculate the gear ratio
-        if v.len() == 2 {
-            gear_ratios.push(v.iter().product());
+            if report.is_valid() {
+                println!("{} is valid", file_path);
+            } else {
+                print!("{}", report);
+            }
         }
+        _ => panic!("Unknown command: {}", args.join(" ")),
     }
-    gear_ratios.iter().sum()
 }
 
-// --- helpers ---
-#[derive(Copy, Clone, Eq, Hash, PartialEq)]
-struct Position(i32, i32);
+// flags come in pairs, each one replacing a set of the default alphabet or a way rows are read
+fn parse_options(flags: &[&str]) -> ParseOptions {
+    let mut options = ParseOptions::default();
 
+    for pair in flags.chunks(2) {
+        let value = *pair.get(1).unwrap_or_else(|| panic!("Missing value after {}", pair[0]));
+
+        match (pair[0], value) {
+            ("--blanks", chars) => options.alphabet.blanks = chars.chars().collect(),
+            ("--symbols", chars) => options.alphabet.symbols = chars.chars().collect(),
+            ("--ignore", chars) => options.alphabet.ignored = chars.chars().collect(),
+            ("--reject", chars) => options.alphabet.rejected = chars.chars().collect(),
+            ("--ragged", "pad") => options.ragged_rows = RaggedRows::Pad,
+            ("--ragged", "reject") => options.ragged_rows = RaggedRows::Reject,
+            ("--ragged", "per-row") => options.ragged_rows = RaggedRows::PerRow,
+            ("--columns", "chars") => options.column_unit = ColumnUnit::Chars,
+            ("--columns", "graphemes") => options.column_unit = ColumnUnit::Graphemes,
+            (flag, value) => panic!("Unknown flag: {} {}", flag, value),
+        }
+    }
 
+    options
+}
 
 fn main() {
+    // commands are only run when arguments are given,
+    // otherwise we solve the puzzle as usual
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+
+    if !args.is_empty() {
+        run_command(&args);
+        return;
+    }
+
     println!("=== Advent of Code 2023 - Day 3 ===\n");
 
     println!("--- Part 1 ---");
 
     println!("PART1: Test file");
-    let result = part_1(EXAMPLE_INPUT_PART_1);
+    let result = part_1(EXAMPLE_INPUT_PART_1, CountingMode::PerNumber);
     let expected = 4361;
     println!("Test result: {} (expected: {})", result, expected);
 
@@ -199,7 +277,35 @@ fn main() {
     }
 
     println!("PART1: Input file");
-    let result = part_1(PUZZLE_INPUT);
+    let result = part_1(PUZZLE_INPUT, CountingMode::PerNumber);
+    println!("Result: {}", result);
+
+    println!("PART1: Number shared by several symbols");
+    let result = part_1(EXAMPLE_INPUT_SHARED_NUMBER, CountingMode::PerNumber);
+    // 12 + 12 + 34
+    let expected = 58;
+    println!("Test result: {} (expected: {})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("PART1: Number shared by several symbols, counted per symbol");
+    let result = part_1(EXAMPLE_INPUT_SHARED_NUMBER, CountingMode::PerAdjacentSymbol);
+    // 12 (*) + 12 (#) + 34 (*, # and $)
+    let expected = 12 + 12 + 34 * 3;
+    println!("Test result: {} (expected: {})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("PART1: Input file, counted per symbol");
+    let result = part_1(PUZZLE_INPUT, CountingMode::PerAdjacentSymbol);
     println!("Result: {}", result);
 
     println!("--- Part 2 ---");
@@ -218,4 +324,883 @@ fn main() {
     println!("PART1: Input file");
     let result = part_2(PUZZLE_INPUT);
     println!("Result: {}", result);
+
+    println!("--- Grid ---");
+
+    println!("GRID: Test file");
+    let grid = Grid::parse(&std::fs::read_to_string(EXAMPLE_INPUT_PART_1).unwrap());
+    let result = (grid.width(), grid.height(), grid.rows().count(), grid.columns().count());
+    let expected = (10, 10, 10, 10);
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("GRID: Bounds-checked access");
+    let result = (
+        grid.get(Position(1, 3)).copied(),
+        grid.get(Position(-1, 0)).copied(),
+        grid.get(Position(0, 10)).copied(),
+        grid.row(0).map(|row| row.iter().collect::<String>()),
+        grid.column(0).collect::<String>(),
+    );
+    let expected = (
+        Some('*'),
+        None,
+        None,
+        Some("467..114..".to_string()),
+        "4...6.....".to_string(),
+    );
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("GRID: Neighborhoods");
+    let corner = Position(0, 0);
+    let result = (
+        grid.neighbors4(corner).count(),
+        grid.neighbors8(corner).count(),
+        grid.neighbors(corner, Neighborhood::Four, Edges::Toroidal).count(),
+        grid.neighbors(corner, Neighborhood::Eight, Edges::Toroidal).collect::<Vec<Position>>(),
+    );
+    let expected = (
+        2,
+        3,
+        4,
+        vec![
+            Position(9, 9),
+            Position(9, 0),
+            Position(9, 1),
+            Position(0, 9),
+            Position(0, 1),
+            Position(1, 9),
+            Position(1, 0),
+            Position(1, 1),
+        ],
+    );
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("GRID: Thin toroidal grid");
+    let mut thin_grid = Grid::filled(2, 1, 0);
+    *thin_grid.get_mut(Position(0, 1)).unwrap() = 1;
+    let result = thin_grid
+    .neighbors(Position(0, 0), Neighborhood::Eight, Edges::Toroidal)
+    .map(|position| *thin_grid.get(position).unwrap())
+    .collect::<Vec<i32>>();
+    let expected = vec![1];
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("--- Number index ---");
+
+    println!("INDEX: Test file");
+    let input = std::fs::read_to_string(EXAMPLE_INPUT_PART_1).unwrap();
+    let square = SquareGrid::parse(&input, Neighborhood::Eight, &ParseOptions::default()).unwrap();
+    let index = NumberIndex::build(&square, SignMode::Unsigned);
+    let result = (
+        index.numbers.len(),
+        index.number_at(Position(0, 1)).map(|id| index.numbers[id].0),
+        index.number_at(Position(0, 3)),
+        index.numbers_around(&square, Position(1, 3)).iter().map(|id| index.numbers[*id].0).collect::<Vec<i64>>(),
+    );
+    let expected = (10, Some(467), None, vec![467, 35]);
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("INDEX: Generated schematic");
+    let schematic = bench::generate_schematic(50, 60, 2023);
+    let result = (sum_part_numbers(&schematic, CountingMode::PerAdjacentSymbol), sum_gear_ratios(&schematic));
+    let expected = bench::naive_sums(&schematic);
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("--- Schematic ---");
+
+    println!("SCHEMATIC: Test file");
+    let schematic = Schematic::parse(&std::fs::read_to_string(EXAMPLE_INPUT_PART_1).unwrap());
+    let values = |number_ids: &[usize]| number_ids.iter().map(|id| schematic.numbers[*id].value).collect::<Vec<i64>>();
+    let first_gear = schematic.symbols.iter().position(|symbol| symbol.kind == '*').unwrap();
+    let number_617 = schematic.numbers.iter().position(|number| number.value == 617).unwrap();
+    let result = (
+        schematic.numbers.len(),
+        schematic.symbols.len(),
+        values(&schematic.unattached_numbers().collect::<Vec<usize>>()),
+        values(&schematic.part_numbers().collect::<Vec<usize>>()).len(),
+        schematic.symbols[first_gear].pos,
+        values(schematic.parts_adjacent_to(first_gear)),
+        schematic.symbols_adjacent_to(number_617).iter().map(|id| schematic.symbols[*id].kind).collect::<String>(),
+        schematic.numbers[number_617].row,
+        schematic.numbers[number_617].col_span.clone(),
+    );
+    let expected = (10, 6, vec![114, 58], 8, Position(1, 3), vec![467, 35], "*".to_string(), 4, 0..3);
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("--- Gear rules ---");
+
+    println!("GEARS: Test file, several rules");
+    let schematic = Schematic::parse(&std::fs::read_to_string(EXAMPLE_INPUT_HUBS).unwrap());
+    let rules = [
+        GearRule::part_2(),
+        GearRule::new("hub", "#", Arity::Exactly(3), Combiner::Product),
+        GearRule::new("spokes", "#*", Arity::AtLeast(2), Combiner::Sum),
+        GearRule::new("largest", "", Arity::AtLeast(1), Combiner::Max),
+        GearRule::new(
+            "squares",
+            "*",
+            Arity::AtLeast(2),
+            Combiner::Custom(Box::new(|values| values.iter().map(|value| value.pow(2)).sum())),
+        ),
+    ];
+    let results = gears::evaluate_rules(&schematic, &rules);
+    let result = results
+    .iter()
+    .map(|result| (result.name.as_str(), result.gears.len(), result.total))
+    .collect::<Vec<(&str, usize, i64)>>();
+    let expected = vec![
+        ("gear", 1, 5 * 6),
+        ("hub", 1, 12 * 3 * 4),
+        ("spokes", 2, 12 + 3 + 4 + 5 + 6),
+        ("largest", 2, 12 + 6),
+        ("squares", 1, 25 + 36),
+    ];
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("GEARS: Input file, several rules");
+    let schematic = Schematic::parse(&std::fs::read_to_string(PUZZLE_INPUT).unwrap());
+    let rules = [
+        GearRule::part_2(),
+        GearRule::new("single", "", Arity::Exactly(1), Combiner::Sum),
+        GearRule::new("any", "", Arity::AtLeast(2), Combiner::Max),
+    ];
+
+    for result in gears::evaluate_rules(&schematic, &rules) {
+        println!("Result: {} for {} ({} gears)", result.total, result.name, result.gears.len());
+    }
+
+    println!("--- Rendering ---");
+
+    println!("RENDER: Test file");
+    let input = std::fs::read_to_string(EXAMPLE_INPUT_PART_1).unwrap();
+    let rendered = render::render(&Schematic::parse(&input), render::RenderOptions::default());
+    let result = render::strip_ansi(&rendered);
+    println!("{}", rendered);
+
+    if result == input.trim_end()
+        && rendered.contains("\x1b[32m467")
+        && rendered.contains("\x1b[31m114")
+        && rendered.contains("\x1b[1;35m*")
+        && rendered.contains("\x1b[1;33m#")
+    {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("RENDER: Test file, row totals and legend");
+    let options = render::RenderOptions { legend: true, row_totals: true };
+    let rendered = render::render(&Schematic::parse(&input), options);
+    let stripped = render::strip_ansi(&rendered);
+    let lines = stripped.lines().collect::<Vec<&str>>();
+    let result = (lines[0], lines[1], lines.len());
+    let expected = (
+        "467..114..  parts:    467  gears:        0",
+        "...*......  parts:      0  gears:    16345",
+        12,
+    );
+    println!("{}", rendered);
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("--- Image export ---");
+
+    println!("IMAGE: Test file, SVG");
+    let schematic = Schematic::parse(&std::fs::read_to_string(EXAMPLE_INPUT_PART_1).unwrap());
+    let svg = image::to_svg(&schematic, image::ImageOptions::default()).unwrap();
+    let result = (
+        svg.matches("class=\"number part\"").count(),
+        svg.matches("class=\"number unattached\"").count(),
+        svg.matches("class=\"gear\"").count(),
+        svg.matches("class=\"symbol\"").count(),
+        svg.matches("class=\"link\"").count(),
+        svg.contains(">467</text>"),
+    );
+    let links = (0..schematic.symbols.len()).map(|id| schematic.parts_adjacent_to(id).len()).sum::<usize>();
+    let expected = (8, 2, 2, 4, links, true);
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("IMAGE: Test file, SVG without links");
+    let svg = image::to_svg(&schematic, image::ImageOptions { links: false, cell_size: 10 }).unwrap();
+    let result = (svg.matches("class=\"link\"").count(), svg.contains("width=\"100\" height=\"100\""));
+    let expected = (0, true);
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("IMAGE: Test file, PNG");
+    let bytes = image::to_png(&schematic, image::ImageOptions::default()).unwrap();
+    let decoder = png::Decoder::new(bytes.as_slice());
+    let mut reader = decoder.read_info().unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).unwrap();
+    // center of the gear at row 1, column 3
+    let pixel_index = ((16 + 8) * info.width as usize + 3 * 16 + 8) * 3;
+    let result = (info.width, info.height, pixels[pixel_index..pixel_index + 3].to_vec());
+    let expected = (160, 160, vec![220, 150, 230]);
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("IMAGE: Empty schematic and zero cell size");
+    let empty = Schematic::parse("");
+    let bytes = image::to_png(&empty, image::ImageOptions::default()).unwrap();
+    let info = png::Decoder::new(bytes.as_slice()).read_info().unwrap().info().clone();
+    let result = (
+        (info.width, info.height),
+        image::to_png(&schematic, image::ImageOptions { links: true, cell_size: 0 }),
+        image::to_svg(&schematic, image::ImageOptions { links: true, cell_size: 0 }),
+    );
+    let expected = ((16, 16), Err("Cell size must be at least 1 pixel".to_string()), Err("Cell size must be at least 1 pixel".to_string()));
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("--- Adjacency graph ---");
+
+    println!("GRAPH: Test file, nodes and edges");
+    let schematic = Schematic::parse(&std::fs::read_to_string(EXAMPLE_INPUT_PART_1).unwrap());
+    let adjacency = graph::Graph::from_schematic(&schematic);
+    let json = serde_json::from_str::<serde_json::Value>(&adjacency.to_json()).unwrap();
+    let result = (
+        adjacency.nodes.len(),
+        adjacency.edges.len(),
+        json["nodes"][0]["label"].as_str().unwrap().to_string(),
+        json["nodes"][10]["kind"].as_str().unwrap().to_string(),
+        json["edges"][0]["target"].as_str().unwrap().to_string(),
+    );
+    let expected = (16, 8, "467".to_string(), "symbol".to_string(), "n0".to_string());
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("GRAPH: Test file, DOT and GraphML");
+    let dot = adjacency.to_dot();
+    let graphml = adjacency.to_graphml();
+    let result = (
+        dot.matches(" -- ").count(),
+        dot.contains("s0 [label=\"*\", shape=circle, row=1, col=3];"),
+        graphml.matches("<node ").count(),
+        graphml.matches("<edge ").count(),
+        graphml.contains("<edge source=\"s0\" target=\"n0\"/>"),
+    );
+    let expected = (8, true, 16, 8, true);
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("GRAPH: Test file, part components");
+    let components = graph::part_components(&schematic)
+    .iter()
+    .map(|component| component.iter().map(|number_id| schematic.numbers[*number_id].value).collect())
+    .collect::<Vec<Vec<i64>>>();
+    let expected = vec![vec![467, 35], vec![633], vec![617], vec![592], vec![755, 598], vec![664]];
+    println!("Test result: {:?} (expected: {:?})", components, expected);
+
+    if components == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("GRAPH: Hubs file, part components");
+    let schematic = Schematic::parse(&std::fs::read_to_string(EXAMPLE_INPUT_HUBS).unwrap());
+    let components = graph::part_components(&schematic)
+    .iter()
+    .map(|component| component.iter().map(|number_id| schematic.numbers[*number_id].value).collect())
+    .collect::<Vec<Vec<i64>>>();
+    let expected = vec![vec![12, 3, 4], vec![5, 6]];
+    println!("Test result: {:?} (expected: {:?})", components, expected);
+
+    if components == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("--- Signed numbers ---");
+
+    println!("SIGNED: Signed file, unsigned mode");
+    let options = ParseOptions { sign_mode: SignMode::Unsigned, ..ParseOptions::default() };
+    let schematic = Schematic::parse_with(&std::fs::read_to_string(EXAMPLE_INPUT_SIGNED).unwrap(), &options).unwrap();
+    let result = (
+        schematic.numbers.iter().map(|number| number.value).collect::<Vec<i64>>(),
+        schematic.symbols.iter().map(|symbol| symbol.kind).collect::<String>(),
+        schematic.part_numbers().map(|number_id| schematic.numbers[number_id].value).sum::<i64>(),
+        gears::evaluate_rules(&schematic, &[GearRule::part_2()])[0].total,
+    );
+    // every sign is a symbol, touching the number right after it
+    let expected = (vec![12, 5, 3, 4, 7], "-+-*#-".to_string(), 12 + 5 + 3 + 4 + 7, 12 * 7);
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("SIGNED: Signed file, signed mode");
+    let options = ParseOptions { sign_mode: SignMode::Signed, ..ParseOptions::default() };
+    let schematic = Schematic::parse_with(&std::fs::read_to_string(EXAMPLE_INPUT_SIGNED).unwrap(), &options).unwrap();
+    let result = (
+        schematic.numbers.iter().map(|number| number.value).collect::<Vec<i64>>(),
+        schematic.numbers.iter().map(|number| number.col_span.clone()).collect::<Vec<std::ops::Range<usize>>>(),
+        schematic.symbols.iter().map(|symbol| symbol.kind).collect::<String>(),
+        schematic.part_numbers().map(|number_id| schematic.numbers[number_id].value).sum::<i64>(),
+        gears::evaluate_rules(&schematic, &[GearRule::part_2()])[0].total,
+    );
+    // the '-' of "3-4" follows a digit so it stays a symbol, "+5" no longer touches anything
+    let expected = (
+        vec![-12, 5, 3, 4, -7],
+        vec![0..3, 5..7, 9..10, 11..12, 2..4],
+        "-*#".to_string(),
+        -12 + 3 + 4 - 7,
+        -12 * -7,
+    );
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("SIGNED: Input file, both modes");
+    let input = std::fs::read_to_string(PUZZLE_INPUT).unwrap();
+    let unsigned = Schematic::parse(&input);
+    let signed = Schematic::parse_with(&input, &options).unwrap();
+    let negative_numbers = signed.numbers.iter().filter(|number| number.value < 0).count();
+    let result = (
+        unsigned.numbers.len() == signed.numbers.len(),
+        unsigned.symbols.len() - signed.symbols.len() >= negative_numbers,
+        negative_numbers > 0,
+    );
+    // the same numbers, some of them negative, each sign taken away from the symbols
+    let expected = (true, true, true);
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!(
+        "Result: {} part numbers, {} gear ratios ({} negative numbers)",
+        signed.part_numbers().map(|number_id| signed.numbers[number_id].value).sum::<i64>(),
+        gears::evaluate_rules(&signed, &[GearRule::part_2()])[0].total,
+        negative_numbers
+    );
+
+    println!("--- Alphabet ---");
+
+    println!("ALPHABET: Alphabet file, default alphabet");
+    let input = std::fs::read_to_string(EXAMPLE_INPUT_ALPHABET).unwrap();
+    let schematic = Schematic::parse(&input);
+    let result = (
+        schematic.symbols.iter().map(|symbol| symbol.kind).collect::<String>(),
+        sum_part_numbers(&input, CountingMode::PerNumber),
+    );
+    // letters, spaces and accents are all symbols
+    let expected = ("a* é".to_string(), 12 + 7 + 34);
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("ALPHABET: Alphabet file, custom alphabet");
+    let options = ParseOptions {
+        alphabet: Alphabet::new(".", "*#", " ", ""),
+        ..ParseOptions::default()
+    };
+    let schematic = Schematic::parse_with(&input, &options).unwrap();
+    let report = alphabet::validate(&schematic.grid, &options.alphabet);
+    let result = (
+        schematic.symbols.iter().map(|symbol| symbol.kind).collect::<String>(),
+        schematic.part_numbers().map(|number_id| schematic.numbers[number_id].value).sum::<i64>(),
+        report.to_string(),
+    );
+    // 7 only touched the 'é', now read as a blank
+    let expected = (
+        "*".to_string(),
+        12 + 34,
+        "unexpected character 'a' at row 1, column 3\nunexpected character 'é' at row 2, column 7\n".to_string(),
+    );
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("ALPHABET: Alphabet file, rejected characters");
+    let options = parse_options(&["--symbols", "*#", "--reject", "é"]);
+    let result = match Schematic::parse_with(&input, &options) {
+        Ok(_) => None,
+        Err(report) => Some((report.is_rejected(), report.issues.len(), report.issues[2].to_string())),
+    };
+    let expected = Some((true, 3, "rejected character 'é' at row 2, column 7".to_string()));
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("ALPHABET: Input file, puzzle symbols only");
+    let grid = Grid::parse(&std::fs::read_to_string(PUZZLE_INPUT).unwrap());
+    let result = alphabet::validate(&grid, &Alphabet::new(".", "#$%&*+-/=@", "", "")).is_valid();
+    let expected = true;
+    println!("Test result: {} (expected: {})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("--- Ragged rows ---");
+
+    println!("RAGGED: Ragged file, default options");
+    let input = std::fs::read_to_string(EXAMPLE_INPUT_RAGGED).unwrap();
+    let default_options = ParseOptions::default();
+    let schematic = Schematic::parse(&input);
+    let editor = editor::EditableSchematic::new(&input);
+    let result = (
+        schematic.part_numbers().map(|number_id| schematic.numbers[number_id].value).sum::<i64>(),
+        editor.part_numbers(),
+        topology_sums(&SquareGrid::parse(&input, Neighborhood::Eight, &default_options).unwrap(), &default_options).0,
+        topology_sums(&HexGrid::parse(&input, &default_options).unwrap(), &default_options).0 > 0,
+    );
+    let expected = (467 + 35 + 633 + 5, 467 + 35 + 633 + 5, 467 + 35 + 633 + 5, true);
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("RAGGED: Ragged file, rejected");
+    let options = parse_options(&["--ragged", "reject"]);
+    let result = match Schematic::parse_with(&input, &options) {
+        Ok(_) => vec![],
+        Err(report) => report.issues.iter().map(|issue| issue.to_string()).collect(),
+    };
+    let expected = vec!["row 2 has 10 columns instead of 8", "row 3 has 10 columns instead of 8"];
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("RAGGED: Ragged file, padded");
+    let options = parse_options(&["--ragged", "pad"]);
+    let schematic = Schematic::parse_with(&input, &options).unwrap();
+    let result = (
+        schematic.grid.row_width(0),
+        schematic.grid.iter().count(),
+        schematic.numbers[2].col_span.clone(),
+        schematic.part_numbers().map(|number_id| schematic.numbers[number_id].value).sum::<i64>(),
+        gears::evaluate_rules(&schematic, &[GearRule::part_2()])[0].total,
+    );
+    // "35" comes after the 2 bytes of 'é' but starts at column 2, the combining accent is a symbol of its own
+    let expected = (Some(10), 40, 2..4, 467 + 35 + 633 + 5, 467 * 35);
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("RAGGED: Ragged file, per row");
+    let options = parse_options(&["--ragged", "per-row"]);
+    let schematic = Schematic::parse_with(&input, &options).unwrap();
+    let result = (
+        schematic.grid.row_width(0),
+        schematic.grid.row_width(3),
+        schematic.grid.iter().count(),
+        schematic.grid.column(9).count(),
+        schematic.part_numbers().map(|number_id| schematic.numbers[number_id].value).sum::<i64>(),
+        render::strip_ansi(&render::render(&schematic, render::RenderOptions::default())).lines().next().unwrap().to_string(),
+    );
+    let expected = (Some(8), Some(8), 36, 2, 467 + 35 + 633 + 5, "467..114".to_string());
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("RAGGED: Ragged file, graphemes");
+    let options = parse_options(&["--ragged", "per-row", "--columns", "graphemes"]);
+    let schematic = Schematic::parse_with(&input, &options).unwrap();
+    let result = (
+        schematic.grid.row_width(3),
+        schematic.symbols.iter().map(|symbol| (symbol.kind, symbol.pos.1)).collect::<Vec<(char, i32)>>(),
+        schematic.part_numbers().map(|number_id| schematic.numbers[number_id].value).sum::<i64>(),
+    );
+    // the accent now belongs to its 'e', moving "5#" one column to the left
+    let expected = (Some(7), vec![('*', 3), ('é', 0), ('é', 3), ('#', 6)], 467 + 35 + 633 + 5);
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("RAGGED: Grapheme cells");
+    // a decomposed 'é', and an 'x' in an enclosing circle, which has no composed form
+    let result = ColumnUnit::Graphemes.split("1e\u{301}2x\u{20dd}3");
+    let expected = vec!['1', 'é', '2', '\u{20dd}', '3'];
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected && Alphabet::default().is_symbol(result[3]) {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("--- Streaming ---");
+
+    println!("STREAM: Test file");
+    let input = std::fs::read_to_string(EXAMPLE_INPUT_PART_1).unwrap();
+    let result = streaming::solve(input.as_bytes()).unwrap();
+    let expected = streaming::StreamTotals { rows: 10, part_numbers: 4361, gear_ratios: 467835 };
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("STREAM: Input and generated files, compared with the schematic");
+    let inputs = [
+        std::fs::read_to_string(PUZZLE_INPUT).unwrap(),
+        std::fs::read_to_string(EXAMPLE_INPUT_SHARED_NUMBER).unwrap(),
+        std::fs::read_to_string(EXAMPLE_INPUT_HUBS).unwrap(),
+        bench::generate_schematic(200, 150, 48),
+        bench::generate_schematic(1, 40, 48),
+        String::new(),
+    ];
+    let result = inputs
+    .iter()
+    .map(|input| {
+        let totals = streaming::solve(input.as_bytes()).unwrap();
+        (totals.part_numbers, totals.gear_ratios)
+    })
+    .collect::<Vec<(i64, i64)>>();
+    let expected = inputs
+    .iter()
+    .map(|input| (sum_part_numbers(input, CountingMode::PerNumber), sum_gear_ratios(input)))
+    .collect::<Vec<(i64, i64)>>();
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("STREAM: Test file repeated over a million rows");
+    // the first and last rows of the test file have no symbols, so copies do not touch each other
+    let repeated = format!("{}\n", input.trim_end()).repeat(100_000);
+    let result = streaming::solve(std::io::BufReader::new(repeated.as_bytes())).unwrap();
+    let expected = streaming::StreamTotals {
+        rows: 1_000_000,
+        part_numbers: 4361 * 100_000,
+        gear_ratios: 467835 * 100_000,
+    };
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("--- Editing ---");
+
+    println!("EDIT: Test file, single edits");
+    let input = std::fs::read_to_string(EXAMPLE_INPUT_PART_1).unwrap();
+    let mut schematic = editor::EditableSchematic::new(&input);
+    let mut result = vec![(schematic.part_numbers(), schematic.gear_ratios())];
+    // 114 becomes a part number
+    schematic.set(1, 6, '+').unwrap();
+    result.push((schematic.part_numbers(), schematic.gear_ratios()));
+    // 35 becomes 3, still next to the '*' it shares with 467
+    schematic.set(2, 3, '.').unwrap();
+    result.push((schematic.part_numbers(), schematic.gear_ratios()));
+    // the '*' next to 617 becomes a 3, leaving 6173 without any symbol
+    schematic.set(4, 3, '3').unwrap();
+    result.push((schematic.part_numbers(), schematic.gear_ratios()));
+    let expected = vec![
+        (4361, 467835),
+        (4361 + 114, 467835),
+        (4361 + 114 - 35 + 3, 467835 - 467 * 35 + 467 * 3),
+        (4361 + 114 - 35 + 3 - 617, 467835 - 467 * 35 + 467 * 3),
+    ];
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("EDIT: Generated schematic, random edits");
+    let mut rng = ChaCha8Rng::seed_from_u64(49);
+    let input = bench::generate_schematic(30, 40, 49);
+    let mut schematic = editor::EditableSchematic::new(&input);
+    let alphabet = ['.', '.', '.', '*', '*', '#', '1', '2', '5', '9'];
+    let mut mismatches = 0;
+
+    for _ in 0..2000 {
+        let (row, col) = (rng.gen_range(0..30), rng.gen_range(0..40));
+        schematic.set(row, col, alphabet[rng.gen_range(0..alphabet.len())]).unwrap();
+
+        // the incremental totals must always match a full recomputation
+        let text = schematic.to_text();
+        let full = (sum_part_numbers(&text, CountingMode::PerNumber), sum_gear_ratios(&text));
+
+        if (schematic.part_numbers(), schematic.gear_ratios()) != full {
+            mismatches += 1;
+        }
+    }
+
+    let result = (mismatches, schematic.set(30, 0, '.').is_err(), schematic.set(0, 0, '\n').is_err());
+    let expected = (0, true, true);
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("--- Topologies ---");
+
+    println!("TOPOLOGY: Test and input files, square grid");
+    let inputs = [
+        std::fs::read_to_string(EXAMPLE_INPUT_PART_1).unwrap(),
+        std::fs::read_to_string(PUZZLE_INPUT).unwrap(),
+    ];
+    let result = inputs
+    .iter()
+    .map(|input| topology_sums(&SquareGrid::parse(input, Neighborhood::Eight, &default_options).unwrap(), &default_options))
+    .collect::<Vec<(i64, i64)>>();
+    let expected = inputs
+    .iter()
+    .map(|input| (sum_part_numbers(input, CountingMode::PerNumber), sum_gear_ratios(input)))
+    .collect::<Vec<(i64, i64)>>();
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("TOPOLOGY: Test file, square grid with four neighbors");
+    let result = topology_sums(&SquareGrid::parse(&inputs[0], Neighborhood::Four, &default_options).unwrap(), &default_options);
+    // only the numbers right above, below or beside a symbol, and no gear
+    let expected = (35 + 633 + 617 + 664 + 598, 0);
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("TOPOLOGY: Hex file, all topologies");
+    let input = std::fs::read_to_string(EXAMPLE_INPUT_HEX).unwrap();
+    let result = vec![
+        topology_sums(&SquareGrid::parse(&input, Neighborhood::Eight, &default_options).unwrap(), &default_options),
+        topology_sums(&SquareGrid::parse(&input, Neighborhood::Four, &default_options).unwrap(), &default_options),
+        topology_sums(&HexGrid::parse(&input, &default_options).unwrap(), &default_options),
+    ];
+    // on the hex grid the '*' only touches the 5 above right and the 3 below left
+    let expected = vec![(12 + 5 + 3 + 4, 0), (0, 0), (5 + 3, 5 * 3)];
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("TOPOLOGY: Voxel file, several rules");
+    let voxels = VoxelGrid::parse(&std::fs::read_to_string(EXAMPLE_INPUT_VOXEL).unwrap(), &default_options).unwrap();
+    let schematic = TopologySchematic::build(&voxels, &default_options);
+    let rules = [
+        GearRule::part_2(),
+        GearRule::new("largest", "", Arity::AtLeast(1), Combiner::Max),
+    ];
+    let result = (
+        voxels.layers.len(),
+        schematic.symbols.clone(),
+        schematic.sum_part_numbers(),
+        schematic
+        .evaluate_rules(&rules)
+        .iter()
+        .map(|result| (result.name.clone(), result.total))
+        .collect::<Vec<(String, i64)>>(),
+    );
+    // the '*' of the middle layer touches 12 and 7 on the first layer, the 9 of the last one is two columns away
+    let expected = (
+        3,
+        vec![('*', topology::Voxel(1, 1, 1))],
+        12 + 7,
+        vec![("gear".to_string(), 12 * 7), ("largest".to_string(), 12)],
+    );
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("TOPOLOGY: Parse options, square grid");
+    let input = "-5*.\n7..#\n";
+    let signed = ParseOptions {
+        sign_mode: SignMode::Signed,
+        ..ParseOptions::default()
+    };
+    let signed_hash_only = ParseOptions {
+        alphabet: Alphabet::new(".", "#", "", ""),
+        ..signed.clone()
+    };
+    let result = [&default_options, &signed, &signed_hash_only]
+    .iter()
+    .map(|options| topology_sums(&SquareGrid::parse(input, Neighborhood::Eight, options).unwrap(), options).0)
+    .collect::<Vec<i64>>();
+    let expected = vec![5 + 7, -5, 0];
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("TOPOLOGY: Test file, schematic over a square grid with four neighbors");
+    let square = SquareGrid::parse(&inputs[0], Neighborhood::Four, &default_options).unwrap();
+    let schematic = Schematic::from_square_grid(square, &default_options);
+    let result = (
+        schematic.part_numbers().map(|number_id| schematic.numbers[number_id].value).sum::<i64>(),
+        render::strip_ansi(&render::render(&schematic, render::RenderOptions::default())).lines().count(),
+    );
+    let expected = (35 + 633 + 617 + 664 + 598, 10);
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
+
+    println!("TOPOLOGY: Voxel layers split by several blank lines");
+    let input = "12.\n..*\n\n\n\n.3.\n\r\n";
+    let voxels = VoxelGrid::parse(input, &default_options).unwrap();
+    let result = (voxels.layers.len(), topology_sums(&voxels, &default_options));
+    let expected = (2, (12 + 3, 12 * 3));
+    println!("Test result: {:?} (expected: {:?})", result, expected);
+
+    if result == expected {
+        println!("Test successful!");
+    } else {
+        panic!("Test failed!");
+    }
 }
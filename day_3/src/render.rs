@@ -0,0 +1,158 @@
+use std::collections::HashSet;
+
+use crate::{
+    gears::{self, GearRule},
+    grid::Grid,
+    schematic::Schematic,
+    Position,
+};
+
+// --- styles ---
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Style {
+    Blank,
+    PartNumber,
+    UnattachedNumber,
+    Symbol,
+    Gear,
+}
+
+impl Style {
+    fn code(&self) -> &'static str {
+        match self {
+            // dim
+            Style::Blank => "\x1b[2m",
+            // green
+            Style::PartNumber => "\x1b[32m",
+            // red
+            Style::UnattachedNumber => "\x1b[31m",
+            // bold yellow
+            Style::Symbol => "\x1b[1;33m",
+            // bold magenta on the gears of part 2
+            Style::Gear => "\x1b[1;35m",
+        }
+    }
+}
+
+// --- rendering ---
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenderOptions {
+    pub legend: bool,
+    // part numbers and gear ratios of each row, after the row
+    pub row_totals: bool,
+}
+
+pub fn render(schematic: &Schematic, options: RenderOptions) -> String {
+    let grid = &schematic.grid;
+    let mut styles = Grid::filled(grid.width(), grid.height(), Style::Blank);
+
+    // coloring numbers, depending on whether they touch a symbol
+    for (number_id, number) in schematic.numbers.iter().enumerate() {
+        let style = if schematic.symbols_adjacent_to(number_id).is_empty() {
+            Style::UnattachedNumber
+        } else {
+            Style::PartNumber
+        };
+
+        for col in number.col_span.clone() {
+            if let Some(cell) = styles.get_mut(Position(number.row as i32, col as i32)) {
+                *cell = style;
+            }
+        }
+    }
+
+    // and symbols, with the gears found by part 2 standing out
+    let gears = &gears::evaluate_rules(schematic, &[GearRule::part_2()])[0];
+    let gear_ids = gears.gears.iter().map(|(gear_id, _)| *gear_id).collect::<HashSet<usize>>();
+
+    for (symbol_id, symbol) in schematic.symbols.iter().enumerate() {
+        let style = if gear_ids.contains(&symbol_id) {
+            Style::Gear
+        } else {
+            Style::Symbol
+        };
+
+        *styles.get_mut(symbol.pos).unwrap() = style;
+    }
+
+    // totals of each row, added up once for the whole schematic
+    let mut row_parts = vec![0; grid.height()];
+    let mut row_ratios = vec![0; grid.height()];
+
+    if options.row_totals {
+        for number_id in schematic.part_numbers() {
+            let number = &schematic.numbers[number_id];
+            row_parts[number.row] += number.value;
+        }
+
+        for (symbol_id, ratio) in &gears.gears {
+            row_ratios[schematic.symbols[*symbol_id].pos.0 as usize] += ratio;
+        }
+    }
+
+    let mut lines: Vec<String> = vec![];
+
+    for (row, (cells, cell_styles)) in grid.rows().zip(styles.rows()).enumerate() {
+        let mut line = String::new();
+        let mut current_style: Option<Style> = None;
+
+        // escape codes are only written when the style changes
+        for (ch, style) in cells.iter().zip(cell_styles) {
+            if current_style != Some(*style) {
+                if current_style.is_some() {
+                    line.push_str(RESET);
+                }
+
+                line.push_str(style.code());
+                current_style = Some(*style);
+            }
+
+            line.push(*ch);
+        }
+
+        line.push_str(RESET);
+
+        if options.row_totals {
+            line.push_str(&format!("  parts: {:>6}  gears: {:>8}", row_parts[row], row_ratios[row]));
+        }
+
+        lines.push(line);
+    }
+
+    if options.legend {
+        lines.push(String::new());
+        lines.push(format!(
+            "{}123{} part number  {}123{} unattached number  {}#{} symbol  {}*{} gear",
+            Style::PartNumber.code(),
+            RESET,
+            Style::UnattachedNumber.code(),
+            RESET,
+            Style::Symbol.code(),
+            RESET,
+            Style::Gear.code(),
+            RESET,
+        ));
+    }
+
+    lines.join("\n")
+}
+
+// removes the escape codes, leaving the text as it would be read
+pub fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::new();
+    let mut in_escape = false;
+
+    for ch in text.chars() {
+        match (in_escape, ch) {
+            (false, '\x1b') => in_escape = true,
+            (false, _) => stripped.push(ch),
+            // escape codes used here all end with 'm'
+            (true, 'm') => in_escape = false,
+            (true, _) => (),
+        }
+    }
+
+    stripped
+}
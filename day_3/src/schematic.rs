@@ -0,0 +1,132 @@
+use std::ops::Range;
+
+use crate::{
+    alphabet::{self, Alphabet, Issue, ValidationReport},
+    grid::{ColumnUnit, Grid, Neighborhood, RaggedRows},
+    topology::{SquareGrid, TopologySchematic},
+    Position,
+};
+
+// --- schematic model ---
+// a number of the schematic, whether it touches a symbol or not
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartNumber {
+    pub value: i64,
+    pub row: usize,
+    // columns covered by the digits and the sign if any, end excluded
+    pub col_span: Range<usize>,
+}
+
+// how a '-' or '+' right before digits is read
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SignMode {
+    // as a symbol, next to an unsigned number, as the puzzle expects
+    #[default]
+    Unsigned,
+    // as the sign of the number, unless a digit comes before it ("12-34" is still a symbol)
+    Signed,
+}
+
+// how the characters of a schematic are read
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    pub sign_mode: SignMode,
+    pub alphabet: Alphabet,
+    pub ragged_rows: RaggedRows,
+    pub column_unit: ColumnUnit,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub kind: char,
+    pub pos: Position,
+}
+
+// numbers and symbols are identified by their index in `numbers` and `symbols`,
+// and linked both ways when a symbol touches a digit of a number, the linking
+// itself being the one of any topology, here over the square grid
+pub struct Schematic {
+    pub grid: Grid<char>,
+    pub numbers: Vec<PartNumber>,
+    pub symbols: Vec<Symbol>,
+    linked: TopologySchematic<Position>,
+}
+
+impl Schematic {
+    pub fn parse(input: &str) -> Self {
+        // the default options reject nothing, ragged rows keeping their own width
+        Schematic::parse_with(input, &ParseOptions::default()).unwrap_or_else(|report| panic!("Invalid schematic:\n{}", report))
+    }
+
+    // fails when a rejected character or a rejected ragged row is found, unexpected characters are read as blanks,
+    // cells touch their eight neighbors as in the puzzle
+    pub fn parse_with(input: &str, options: &ParseOptions) -> Result<Self, ValidationReport> {
+        let square = SquareGrid::parse(input, Neighborhood::Eight, options)?;
+
+        Ok(Schematic::from_square_grid(square, options))
+    }
+
+    pub fn from_square_grid(square: SquareGrid, options: &ParseOptions) -> Self {
+        let linked = TopologySchematic::build(&square, options);
+
+        // the cells of a number all are in the same row, one after the other
+        let numbers = linked.numbers
+        .iter()
+        .map(|(value, cells)| PartNumber {
+            value: *value,
+            row: cells[0].0 as usize,
+            col_span: cells[0].1 as usize..cells[cells.len() - 1].1 as usize + 1,
+        })
+        .collect::<Vec<PartNumber>>();
+
+        let symbols = linked.symbols
+        .iter()
+        .map(|(kind, pos)| Symbol { kind: *kind, pos: *pos })
+        .collect::<Vec<Symbol>>();
+
+        Schematic {
+            grid: square.grid,
+            numbers,
+            symbols,
+            linked,
+        }
+    }
+
+    pub fn parts_adjacent_to(&self, symbol_id: usize) -> &[usize] {
+        self.linked.parts_adjacent_to(symbol_id)
+    }
+
+    pub fn symbols_adjacent_to(&self, number_id: usize) -> &[usize] {
+        self.linked.symbols_adjacent_to(number_id)
+    }
+
+    // numbers touching at least one symbol
+    pub fn part_numbers(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.numbers.len()).filter(|number_id| !self.symbols_adjacent_to(*number_id).is_empty())
+    }
+
+    // numbers touching no symbol at all
+    pub fn unattached_numbers(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.numbers.len()).filter(|number_id| self.symbols_adjacent_to(*number_id).is_empty())
+    }
+}
+
+// the grid of the input, with the issues that still let it be read,
+// or all of them when one of them is rejected
+pub fn load_grid(input: &str, options: &ParseOptions) -> Result<(Grid<char>, ValidationReport), ValidationReport> {
+    let rows = input.lines().map(|line| options.column_unit.split(line)).collect();
+    // cells added to ragged rows are blanks
+    let fill = options.alphabet.blanks.first().copied().unwrap_or('.');
+
+    let grid = Grid::from_ragged_rows(rows, options.ragged_rows, fill).map_err(|ragged| ValidationReport {
+        issues: ragged.into_iter().map(Issue::Ragged).collect(),
+    })?;
+
+    let report = alphabet::validate(&grid, &options.alphabet);
+
+    if report.is_rejected() {
+        return Err(report);
+    }
+
+    Ok((grid, report))
+}
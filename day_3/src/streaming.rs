@@ -0,0 +1,75 @@
+use std::io::{self, BufRead};
+
+use crate::{alphabet::Alphabet, index, schematic::SignMode};
+
+// --- streaming solver ---
+// adjacency never spans more than one row up or down, so both parts can be solved
+// row after row while only holding the rows above and below the current one,
+// memory then depends on the width of the schematic but not on its height
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StreamTotals {
+    pub rows: usize,
+    // each number counted once, as in part 1
+    pub part_numbers: i64,
+    // '*' touching exactly two numbers, as in part 2
+    pub gear_ratios: i64,
+}
+
+pub fn solve<R: BufRead>(reader: R) -> io::Result<StreamTotals> {
+    let alphabet = Alphabet::default();
+    let mut totals = StreamTotals::default();
+
+    // the window is (above, current, below), a row is handled once the one below it is known
+    let mut above: Vec<char> = vec![];
+    let mut current: Option<Vec<char>> = None;
+
+    for line in reader.lines() {
+        let below = line?.chars().collect::<Vec<char>>();
+
+        if let Some(current) = &current {
+            add_row(&mut totals, &alphabet, [&above, current, &below]);
+        }
+
+        above = current.replace(below).unwrap_or_default();
+    }
+
+    if let Some(current) = &current {
+        add_row(&mut totals, &alphabet, [&above, current, &[]]);
+    }
+
+    Ok(totals)
+}
+
+// adds the part numbers and the gears of the middle row of the window
+fn add_row(totals: &mut StreamTotals, alphabet: &Alphabet, window: [&[char]; 3]) {
+    let numbers = window.map(|row| index::numbers_in_row(row, SignMode::Unsigned));
+    let current = window[1];
+
+    // rows can be shorter than the others, missing cells are blanks
+    let is_symbol_at = |row: &[char], col: usize| row.get(col).is_some_and(|ch| alphabet.is_symbol(*ch));
+
+    for (value, span) in &numbers[1] {
+        let touches_symbol = window
+        .iter()
+        .any(|row| (span.start.saturating_sub(1)..=span.end).any(|col| is_symbol_at(row, col)));
+
+        if touches_symbol {
+            totals.part_numbers += value;
+        }
+    }
+
+    for (col, _) in current.iter().enumerate().filter(|(_, ch)| **ch == '*') {
+        let values = numbers
+        .iter()
+        .flatten()
+        .filter(|(_, span)| span.start <= col + 1 && span.end >= col)
+        .map(|(value, _)| *value)
+        .collect::<Vec<i64>>();
+
+        if values.len() == 2 {
+            totals.gear_ratios += values[0] * values[1];
+        }
+    }
+
+    totals.rows += 1;
+}
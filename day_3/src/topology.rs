@@ -0,0 +1,248 @@
+use std::{fmt::Debug, hash::Hash};
+
+use crate::{
+    alphabet::ValidationReport,
+    gears::{self, GearRule, RuleResult},
+    grid::{Edges, Grid, Neighborhood},
+    index::NumberIndex,
+    schematic::{self, ParseOptions},
+    Position,
+};
+
+// --- topologies ---
+// how the cells of a schematic are laid out and which ones touch each other,
+// numbers are always read along rows, digits of a row next to each other forming one number,
+// grids are read with the same options as `Schematic`
+pub trait Topology {
+    type Cell: Copy + Eq + Hash + Debug;
+
+    // the cells of each row, in reading order
+    fn rows(&self) -> Vec<Vec<Self::Cell>>;
+
+    fn get(&self, cell: Self::Cell) -> Option<char>;
+
+    fn neighbors(&self, cell: Self::Cell) -> Vec<Self::Cell>;
+}
+
+// the puzzle grid, with either eight or four neighbors
+pub struct SquareGrid {
+    pub grid: Grid<char>,
+    pub neighborhood: Neighborhood,
+}
+
+impl SquareGrid {
+    pub fn parse(input: &str, neighborhood: Neighborhood, options: &ParseOptions) -> Result<Self, ValidationReport> {
+        let (grid, _) = schematic::load_grid(input, options)?;
+
+        Ok(SquareGrid { grid, neighborhood })
+    }
+}
+
+impl Topology for SquareGrid {
+    type Cell = Position;
+
+    fn rows(&self) -> Vec<Vec<Position>> {
+        grid_rows(&self.grid)
+    }
+
+    fn get(&self, cell: Position) -> Option<char> {
+        self.grid.get(cell).copied()
+    }
+
+    fn neighbors(&self, cell: Position) -> Vec<Position> {
+        self.grid.neighbors(cell, self.neighborhood, Edges::Bounded).collect()
+    }
+}
+
+// hexagonal cells in axial coordinates: each line is a row r and each column a q,
+// every row being drawn half a cell further right than the one above it,
+// so a cell touches two cells of each row around it and one on each side
+pub struct HexGrid {
+    pub grid: Grid<char>,
+}
+
+impl HexGrid {
+    pub fn parse(input: &str, options: &ParseOptions) -> Result<Self, ValidationReport> {
+        let (grid, _) = schematic::load_grid(input, options)?;
+
+        Ok(HexGrid { grid })
+    }
+}
+
+impl Topology for HexGrid {
+    type Cell = Position;
+
+    fn rows(&self) -> Vec<Vec<Position>> {
+        grid_rows(&self.grid)
+    }
+
+    fn get(&self, cell: Position) -> Option<char> {
+        self.grid.get(cell).copied()
+    }
+
+    fn neighbors(&self, cell: Position) -> Vec<Position> {
+        // (row, column) offsets, that is (r, q)
+        [(-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0)]
+        .iter()
+        .map(|(row_offset, col_offset)| Position(cell.0 + row_offset, cell.1 + col_offset))
+        .filter(|neighbor| self.grid.contains(*neighbor))
+        .collect()
+    }
+}
+
+// (layer, row, column)
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Voxel(pub i32, pub i32, pub i32);
+
+// a 3D schematic given as 2D layers separated by blank lines, stacked in file order,
+// a cell touches the 26 cells of the cube around it
+pub struct VoxelGrid {
+    pub layers: Vec<Grid<char>>,
+}
+
+impl VoxelGrid {
+    // issues are reported with rows counted from the start of their layer
+    pub fn parse(input: &str, options: &ParseOptions) -> Result<Self, ValidationReport> {
+        let mut layers: Vec<Grid<char>> = vec![];
+        let mut layer: Vec<&str> = vec![];
+
+        // any number of blank lines ends a layer, a last empty line closes the last one
+        for line in input.lines().chain([""]) {
+            if !line.trim().is_empty() {
+                layer.push(line);
+            } else if !layer.is_empty() {
+                let (grid, _) = schematic::load_grid(&layer.join("\n"), options)?;
+                layers.push(grid);
+                layer.clear();
+            }
+        }
+
+        Ok(VoxelGrid { layers })
+    }
+}
+
+impl Topology for VoxelGrid {
+    type Cell = Voxel;
+
+    fn rows(&self) -> Vec<Vec<Voxel>> {
+        self.layers
+        .iter()
+        .enumerate()
+        .flat_map(|(layer, grid)| {
+            grid_rows(grid)
+            .into_iter()
+            .map(move |row| row.into_iter().map(|pos| Voxel(layer as i32, pos.0, pos.1)).collect())
+        })
+        .collect()
+    }
+
+    fn get(&self, cell: Voxel) -> Option<char> {
+        if cell.0 < 0 {
+            return None;
+        }
+
+        self.layers.get(cell.0 as usize)?.get(Position(cell.1, cell.2)).copied()
+    }
+
+    fn neighbors(&self, cell: Voxel) -> Vec<Voxel> {
+        let mut neighbors: Vec<Voxel> = vec![];
+
+        for layer_offset in -1..=1 {
+            for row_offset in -1..=1 {
+                for col_offset in -1..=1 {
+                    let neighbor = Voxel(cell.0 + layer_offset, cell.1 + row_offset, cell.2 + col_offset);
+
+                    if neighbor != cell && self.get(neighbor).is_some() {
+                        neighbors.push(neighbor);
+                    }
+                }
+            }
+        }
+
+        neighbors
+    }
+}
+
+fn grid_rows(grid: &Grid<char>) -> Vec<Vec<Position>> {
+    (0..grid.height())
+    .map(|row| {
+        (0..grid.row_width(row).unwrap())
+        .map(|col| Position(row as i32, col as i32))
+        .collect()
+    })
+    .collect()
+}
+
+// --- linked schematic ---
+// numbers and symbols of any topology, linked both ways when a symbol touches
+// a cell of a number, `Schematic` being this over the square grid of the puzzle
+pub struct TopologySchematic<C> {
+    // value and cells of each number
+    pub numbers: Vec<(i64, Vec<C>)>,
+    pub symbols: Vec<(char, C)>,
+    number_symbols: Vec<Vec<usize>>,
+    symbol_numbers: Vec<Vec<usize>>,
+}
+
+impl<C: Copy + Eq + Hash + Debug> TopologySchematic<C> {
+    // only the sign mode and the alphabet are used here, the rest is for parsing the topology
+    pub fn build<T: Topology<Cell = C>>(topology: &T, options: &ParseOptions) -> Self {
+        // indexing numbers by the cells they cover
+        let index = NumberIndex::build(topology, options.sign_mode);
+
+        // storing symbols and their cell, signs read as part of a number are not symbols
+        let symbols = topology
+        .rows()
+        .into_iter()
+        .flatten()
+        .map(|cell| (topology.get(cell).unwrap(), cell))
+        .filter(|(ch, cell)| options.alphabet.is_symbol(*ch) && index.number_at(*cell).is_none())
+        .collect::<Vec<(char, C)>>();
+
+        // linking each symbol with the numbers around it
+        let mut number_symbols = vec![vec![]; index.numbers.len()];
+        let mut symbol_numbers = vec![vec![]; symbols.len()];
+
+        for (symbol_id, (_, cell)) in symbols.iter().enumerate() {
+            for number_id in index.numbers_around(topology, *cell) {
+                number_symbols[number_id].push(symbol_id);
+                symbol_numbers[symbol_id].push(number_id);
+            }
+        }
+
+        TopologySchematic {
+            numbers: index.numbers,
+            symbols,
+            number_symbols,
+            symbol_numbers,
+        }
+    }
+
+    pub fn parts_adjacent_to(&self, symbol_id: usize) -> &[usize] {
+        &self.symbol_numbers[symbol_id]
+    }
+
+    pub fn symbols_adjacent_to(&self, number_id: usize) -> &[usize] {
+        &self.number_symbols[number_id]
+    }
+
+    // each number touching a symbol counted once, as in part 1
+    pub fn sum_part_numbers(&self) -> i64 {
+        self.numbers
+        .iter()
+        .zip(&self.number_symbols)
+        .filter(|(_, symbols)| !symbols.is_empty())
+        .map(|((value, _), _)| value)
+        .sum()
+    }
+
+    pub fn evaluate_rules(&self, rules: &[GearRule]) -> Vec<RuleResult> {
+        let symbols = self
+        .symbols
+        .iter()
+        .zip(&self.symbol_numbers)
+        .map(|((kind, _), parts)| (*kind, parts.as_slice()));
+
+        gears::evaluate_symbols(symbols, |number_id| self.numbers[number_id].0, rules)
+    }
+}